@@ -1,7 +1,6 @@
 //! Functions to access elements in the tree.
 
-use std::collections::HashMap;
-
+use crate::Dictionary;
 use crate::Foam;
 use crate::FoamError;
 
@@ -41,16 +40,101 @@ impl<'a> Foam<'a> {
     /// let error = level1[0].get("1");
     /// assert_eq!(error, Err(FoamError::NotADictionary));
     /// ```
-    pub fn get(&self, key: &str) -> Result<&[Foam<'a>], FoamError> {
+    pub fn get(&self, key: &str) -> Result<&[Foam<'a>], FoamError<'_>> {
+        match self {
+            Foam::Dictionary(inner) => {
+                inner.get(key).ok_or(FoamError::NoSuchKey)
+            }
+            _ => Err(FoamError::NotADictionary),
+        }
+    }
+
+    /// Retrieve the values from the dictionary, like [`Foam::get`], but falling back to keys that
+    /// are regular expressions (quoted keys, like `"(U|k|epsilon)Final"`) when no key matches
+    /// exactly. See [`Dictionary::lookup`] for the precedence rules.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("\"(U|k|epsilon)Final\" 1; pFinal 2;").unwrap();
+    /// assert_eq!(root.get("kFinal"), Err(FoamError::NoSuchKey));
+    /// assert_eq!(root.lookup("kFinal"), Ok([Foam::Value("1")].as_slice()));
+    /// assert_eq!(root.lookup("pFinal"), Ok([Foam::Value("2")].as_slice()));
+    /// ```
+    pub fn lookup(&self, key: &str) -> Result<&[Foam<'a>], FoamError<'_>> {
         match self {
-            Foam::Dictionary(inner) => inner
-                .get(key)
-                .map(|content| content.as_slice())
-                .ok_or(FoamError::NoSuchKey),
+            Foam::Dictionary(inner) => {
+                inner.lookup(key).ok_or(FoamError::NoSuchKey)
+            }
             _ => Err(FoamError::NotADictionary),
         }
     }
 
+    /// Retrieve the values at a path, like `solvers/p/tolerance` or `solvers.p.tolerance`,
+    /// following the syntax of `foamDictionary -entry`. Segments are separated by `/` or `.`, but
+    /// OpenFOAM keys can contain dots too (like `inlet.1` or `"p.*"`), so a segment is first looked
+    /// up as a whole and only split on its dots if that fails. Keys are found like in
+    /// [`Foam::lookup`] (so regular expression keys work) and numbers are positions in lists.
+    /// Patterns are matched against the whole segment too: with a `"p.*"` key, the segment
+    /// `pFinal.tolerance` is a single key.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     solvers { \"p.*\" { tolerance 1e-6; } }
+    ///     vertices ( (0 0 0) (1 0 0) (1 1 0) (0 1 0) );
+    ///     inlet.1 { type wall; }
+    /// ").unwrap();
+    /// let tolerance = root.get_path("solvers/pFinal/tolerance").unwrap();
+    /// assert_eq!(tolerance, [Foam::Value("1e-6")]);
+    /// assert_eq!(root.get_path("vertices/3/1").unwrap(), [Foam::Value("1")]);
+    /// assert_eq!(root.get_path("vertices.3.1").unwrap(), [Foam::Value("1")]);
+    /// assert_eq!(root.get_path("inlet.1/type").unwrap(), [Foam::Value("wall")]);
+    /// ```
+    ///
+    /// If any segment of the path can't be followed, [`FoamError::InvalidPath`] says which one
+    /// and why. Empty segments (like in `a//b`, `/a` or `a/`) are errors too.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("solvers { p { tolerance 1e-6; } }").unwrap();
+    /// assert_eq!(
+    ///     root.get_path("solvers/U/tolerance"),
+    ///     Err(FoamError::InvalidPath {
+    ///         path: "solvers/U/tolerance".into(),
+    ///         segment: "U".into(),
+    ///         reason: "no such key",
+    ///     })
+    /// );
+    ///
+    /// let root = Foam::parse("vertices ( (0 0 0) (1 0 0) );").unwrap();
+    /// assert_eq!(
+    ///     root.get_path("vertices/2/0"),
+    ///     Err(FoamError::InvalidPath {
+    ///         path: "vertices/2/0".into(),
+    ///         segment: "2".into(),
+    ///         reason: "index out of range",
+    ///     })
+    /// );
+    ///
+    /// assert_eq!(
+    ///     root.get_path("vertices//0"),
+    ///     Err(FoamError::InvalidPath {
+    ///         path: "vertices//0".into(),
+    ///         segment: "".into(),
+    ///         reason: "empty key",
+    ///     })
+    /// );
+    /// ```
+    pub fn get_path(&self, path: &str) -> Result<&[Foam<'a>], FoamError<'_>> {
+        let mut values = std::slice::from_ref(self);
+        for segment in path.split('/') {
+            values = follow(values, path, segment)?;
+        }
+        Ok(values)
+    }
+
     /// Retrieve the first element from a dictionary.
     ///
     /// This is a helper function that does the same as above, but returns the first element in the
@@ -67,7 +151,7 @@ impl<'a> Foam<'a> {
     ///
     /// All the same rules for retrieving elements from dictionaries in [`Foam::get`] still
     /// apply.
-    pub fn get_first(&self, key: &str) -> Result<&Foam<'a>, FoamError> {
+    pub fn get_first(&self, key: &str) -> Result<&Foam<'a>, FoamError<'_>> {
         self.get(key).map(|x| &x[0])
     }
 
@@ -85,6 +169,14 @@ impl<'a> Foam<'a> {
     /// assert_eq!(skips_the_list, Ok("2"));
     /// ```
     ///
+    /// Quoted strings ([`Foam::String`]) are also considered values.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("var (1) \"two words\";").unwrap();
+    /// assert_eq!(root.get_first_value("var"), Ok("two words"));
+    /// ```
+    ///
     /// If none of the elements are values, return [`FoamError::NoSuchValue`].
     ///
     /// ```
@@ -94,21 +186,105 @@ impl<'a> Foam<'a> {
     /// let no_values = root.get_first_value("var");
     /// assert_eq!(no_values, Err(FoamError::NoSuchValue))
     /// ```
-    pub fn get_first_value(&self, key: &str) -> Result<&str, FoamError> {
+    pub fn get_first_value(&self, key: &str) -> Result<&str, FoamError<'_>> {
         match self.get(key) {
-            Ok(entries) => {
-                let first =
-                    entries.iter().find(|x| matches!(x, Foam::Value(_)));
-                if let Some(Foam::Value(entry)) = first {
-                    Ok(entry)
-                } else {
-                    Err(FoamError::NoSuchValue)
-                }
-            }
+            Ok(entries) => entries
+                .iter()
+                .find_map(|x| x.as_value().ok())
+                .ok_or(FoamError::NoSuchValue),
             Err(e) => Err(e),
         }
     }
 
+    /// Retrieve the first value of `key` as a number (a `scalar` in OpenFOAM).
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("tolerance 1e-6; name p;").unwrap();
+    /// assert_eq!(root.get_f64("tolerance"), Ok(1e-6));
+    /// assert_eq!(
+    ///     root.get_f64("name"),
+    ///     Err(FoamError::InvalidNumber { key: "name".into(), text: "p" })
+    /// );
+    /// ```
+    pub fn get_f64(&self, key: &str) -> Result<f64, FoamError<'_>> {
+        let text = self.get_first_value(key)?;
+        text.parse().map_err(|_| FoamError::InvalidNumber {
+            key: key.to_string(),
+            text,
+        })
+    }
+
+    /// Retrieve the first value of `key` as an integer (a `label` in OpenFOAM). Numbers with a
+    /// decimal point or an exponent are not integers, even if their value is whole.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("nCorrectors 2; nu 1.0;").unwrap();
+    /// assert_eq!(root.get_i64("nCorrectors"), Ok(2));
+    /// assert_eq!(
+    ///     root.get_i64("nu"),
+    ///     Err(FoamError::InvalidLabel { key: "nu".into(), text: "1.0" })
+    /// );
+    /// ```
+    pub fn get_i64(&self, key: &str) -> Result<i64, FoamError<'_>> {
+        let text = self.get_first_value(key)?;
+        text.parse().map_err(|_| FoamError::InvalidLabel {
+            key: key.to_string(),
+            text,
+        })
+    }
+
+    /// Retrieve the first value of `key` as a switch. Like OpenFOAM, `true`, `yes`, `on`, `y`,
+    /// `t` and `any` are true, while `false`, `no`, `off`, `n`, `f` and `none` are false.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("runTimeModifiable yes; adjustTimeStep maybe;").unwrap();
+    /// assert_eq!(root.get_bool("runTimeModifiable"), Ok(true));
+    /// assert_eq!(
+    ///     root.get_bool("adjustTimeStep"),
+    ///     Err(FoamError::InvalidSwitch { key: "adjustTimeStep".into(), text: "maybe" })
+    /// );
+    /// ```
+    pub fn get_bool(&self, key: &str) -> Result<bool, FoamError<'_>> {
+        let text = self.get_first_value(key)?;
+        switch(text).ok_or_else(|| FoamError::InvalidSwitch {
+            key: key.to_string(),
+            text,
+        })
+    }
+
+    /// Retrieve the first value of `key` as a word: a value that is not quoted.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("solver PCG; title \"two words\";").unwrap();
+    /// assert_eq!(root.get_word("solver"), Ok("PCG"));
+    /// assert_eq!(
+    ///     root.get_word("title"),
+    ///     Err(FoamError::InvalidWord { key: "title".into(), text: "two words" })
+    /// );
+    /// ```
+    pub fn get_word(&self, key: &str) -> Result<&str, FoamError<'_>> {
+        let value = self.get(key)?.iter().find(|x| {
+            matches!(x, Foam::Value(_) | Foam::Calculated(_) | Foam::String(_))
+        });
+        match value {
+            Some(Foam::Value(word)) => Ok(word),
+            Some(Foam::Calculated(word)) => Ok(word),
+            Some(Foam::String(text)) => Err(FoamError::InvalidWord {
+                key: key.to_string(),
+                text,
+            }),
+            _ => Err(FoamError::NoSuchValue),
+        }
+    }
+
     /// Retrieve the first [`Foam::List`] from a dictionary.
     ///
     /// This works similar to [`Foam::get_first_value`], but skips any elements that are not lists.
@@ -132,7 +308,10 @@ impl<'a> Foam<'a> {
     /// let var = root.get_first_list("var");
     /// assert_eq!(var, Err(FoamError::NoSuchValue));
     /// ```
-    pub fn get_first_list(&self, key: &str) -> Result<&[Foam<'a>], FoamError> {
+    pub fn get_first_list(
+        &self,
+        key: &str,
+    ) -> Result<&[Foam<'a>], FoamError<'_>> {
         match self.get(key) {
             Ok(entries) => {
                 let first = entries.iter().find(|x| matches!(x, Foam::List(_)));
@@ -153,10 +332,10 @@ impl<'a> Foam<'a> {
     /// ```
     /// # use foamparser::Foam;
     /// # use foamparser::FoamError;
-    /// # use std::collections::HashMap;
+    /// # use foamparser::Dictionary;
     /// let root = Foam::parse("not_dict 1; dict { a 1; }").unwrap();
     /// let var = root.get_first_dict("dict");
-    /// let inner = HashMap::from([
+    /// let inner = Dictionary::from([
     ///     ("a", vec![Foam::Value("1")])
     /// ]);
     /// assert_eq!(var, Ok(&inner));
@@ -184,13 +363,13 @@ impl<'a> Foam<'a> {
     pub fn get_first_dict(
         &self,
         key: &str,
-    ) -> Result<&HashMap<&str, Vec<Foam<'a>>>, FoamError> {
+    ) -> Result<&Dictionary<'a>, FoamError<'_>> {
         match self.get(key) {
             Ok(entries) => {
                 let first =
                     entries.iter().find(|x| matches!(x, Foam::Dictionary(_)));
                 if let Some(Foam::Dictionary(entry)) = first {
-                    Ok(&entry)
+                    Ok(entry)
                 } else {
                     Err(FoamError::NoSuchValue)
                 }
@@ -204,13 +383,13 @@ impl<'a> Foam<'a> {
     /// ```
     /// # use foamparser::Foam;
     /// # use foamparser::FoamError;
-    /// # use std::collections::HashMap;
+    /// # use foamparser::Dictionary;
     /// let root = Foam::parse("outer { inner 2; var 2; }").unwrap();
     /// let outer = root.get_first("outer").unwrap();
     /// assert_eq!(
     ///     outer.as_dict(),
     ///     Ok(
-    ///         &HashMap::from([
+    ///         &Dictionary::from([
     ///             ("inner", vec![Foam::Value("2")]),
     ///             ("var", vec![Foam::Value("2")])
     ///         ])
@@ -230,14 +409,16 @@ impl<'a> Foam<'a> {
     /// let dict_maybe = var.as_dict();
     /// assert_eq!(dict_maybe, Err(FoamError::NotADictionary))
     /// ```
-    pub fn as_dict(&self) -> Result<&HashMap<&str, Vec<Foam<'a>>>, FoamError> {
+    pub fn as_dict(&self) -> Result<&Dictionary<'a>, FoamError<'_>> {
         match self {
             Foam::Dictionary(inner) => Ok(inner),
             _ => Err(FoamError::NotADictionary),
         }
     }
 
-    /// Treat the current element as a value and return its underlying value.
+    /// Treat the current element as a value and return its underlying value. Quoted strings are
+    /// returned without the quotes, and the results of expressions ([`Foam::Calculated`]) are
+    /// values too.
     ///
     /// ```
     /// # use foamparser::Foam;
@@ -258,10 +439,156 @@ impl<'a> Foam<'a> {
     /// let value = var.as_value();
     /// assert_eq!(value, Err(FoamError::NotAValue))
     /// ```
-    pub fn as_value(&self) -> Result<&'a str, FoamError> {
+    pub fn as_value(&self) -> Result<&str, FoamError<'_>> {
         match self {
-            Foam::Value(inner) => Ok(inner),
+            Foam::Value(inner) | Foam::String(inner) => Ok(inner),
+            Foam::Calculated(inner) => Ok(inner),
             _ => Err(FoamError::NotAValue),
         }
     }
 }
+
+/// Follow a segment of a path: as a whole key or index or, if that fails, split on each of its
+/// dots in turn. The error is the one for the first split that can't be followed, so
+/// `solvers.U.tolerance` reports `U` as the missing key.
+fn follow<'f, 'a>(
+    values: &'f [Foam<'a>],
+    path: &str,
+    segment: &str,
+) -> Result<&'f [Foam<'a>], FoamError<'static>> {
+    let error = match step(values, path, segment) {
+        Ok(values) => return Ok(values),
+        Err(error) => error,
+    };
+    let mut first_error = None;
+    for (dot, _) in segment.match_indices('.') {
+        let (head, rest) = (&segment[..dot], &segment[dot + 1..]);
+        if head.is_empty() || rest.is_empty() {
+            continue;
+        }
+        match step(values, path, head)
+            .and_then(|values| follow(values, path, rest))
+        {
+            Ok(values) => return Ok(values),
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(first_error.unwrap_or(error))
+}
+
+/// Follow a single key or index, without splitting it.
+fn step<'f, 'a>(
+    values: &'f [Foam<'a>],
+    path: &str,
+    segment: &str,
+) -> Result<&'f [Foam<'a>], FoamError<'static>> {
+    let invalid = |reason| FoamError::InvalidPath {
+        path: path.to_string(),
+        segment: segment.to_string(),
+        reason,
+    };
+    if segment.is_empty() {
+        return Err(invalid("empty key"));
+    }
+    let container = values
+        .iter()
+        .find(|value| matches!(value, Foam::Dictionary(_) | Foam::List(_)));
+    match container {
+        Some(Foam::Dictionary(dict)) => {
+            dict.lookup(segment).ok_or_else(|| invalid("no such key"))
+        }
+        Some(Foam::List(items)) => {
+            let index = segment
+                .parse::<usize>()
+                .map_err(|_| invalid("lists can only be indexed by numbers"))?;
+            let item = items
+                .get(index)
+                .ok_or_else(|| invalid("index out of range"))?;
+            Ok(std::slice::from_ref(item))
+        }
+        _ => Err(invalid("not a dictionary or a list")),
+    }
+}
+
+/// Read a switch the way OpenFOAM does: `true`, `yes`, `on`, `y`, `t` and `any` are true, while
+/// `false`, `no`, `off`, `n`, `f` and `none` are false.
+pub(crate) fn switch(text: &str) -> Option<bool> {
+    match text {
+        "true" | "yes" | "on" | "y" | "t" | "any" => Some(true),
+        "false" | "no" | "off" | "n" | "f" | "none" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Foam;
+    use crate::FoamError;
+
+    fn invalid(
+        path: &str,
+        segment: &str,
+        reason: &'static str,
+    ) -> FoamError<'static> {
+        FoamError::InvalidPath {
+            path: path.into(),
+            segment: segment.into(),
+            reason,
+        }
+    }
+
+    #[test]
+    fn empty_segments() {
+        let root = Foam::parse("a { b 1; }").unwrap();
+        assert_eq!(root.get_path("a/b"), Ok([Foam::Value("1")].as_slice()));
+        assert_eq!(
+            root.get_path("/a/b"),
+            Err(invalid("/a/b", "", "empty key"))
+        );
+        assert_eq!(
+            root.get_path("a/b/"),
+            Err(invalid("a/b/", "", "empty key"))
+        );
+        assert_eq!(
+            root.get_path("a//b"),
+            Err(invalid("a//b", "", "empty key"))
+        );
+        assert_eq!(root.get_path(""), Err(invalid("", "", "empty key")));
+    }
+
+    #[test]
+    fn dotted_paths() {
+        let root = Foam::parse(
+            "a { b 1; c { d 2; } } \"p.*\" { c 3; } inlet.1 4; v ((0 1) (2 3));",
+        )
+        .unwrap();
+        assert_eq!(root.get_path("a.b"), Ok([Foam::Value("1")].as_slice()));
+        assert_eq!(root.get_path("a.c.d"), Ok([Foam::Value("2")].as_slice()));
+        assert_eq!(root.get_path("a/c.d"), Ok([Foam::Value("2")].as_slice()));
+        assert_eq!(root.get_path("v.1.0"), Ok([Foam::Value("2")].as_slice()));
+        assert_eq!(root.get_path("p.*/c"), Ok([Foam::Value("3")].as_slice()));
+        // The whole segment matches the pattern before it is split.
+        assert_eq!(
+            root.get_path("pFinal.c/c"),
+            Ok([Foam::Value("3")].as_slice())
+        );
+        assert_eq!(root.get_path("inlet.1"), Ok([Foam::Value("4")].as_slice()));
+        assert_eq!(
+            root.get_path("a.x.d"),
+            Err(invalid("a.x.d", "x", "no such key"))
+        );
+        assert_eq!(
+            root.get_path("a."),
+            Err(invalid("a.", "a.", "no such key"))
+        );
+    }
+
+    #[test]
+    fn literal_dotted_keys_win() {
+        let root = Foam::parse("a { b 1; } a.b 2;").unwrap();
+        assert_eq!(root.get_path("a.b"), Ok([Foam::Value("2")].as_slice()));
+        assert_eq!(root.get_path("a/b"), Ok([Foam::Value("1")].as_slice()));
+    }
+}
@@ -0,0 +1,751 @@
+//! Evaluates the expressions of `#calc` and `#eval` directives.
+//!
+//! OpenFOAM compiles `#calc` expressions as C++; instead, we support the subset people usually
+//! write: arithmetic and comparison operators, the usual math functions and vectors. All numbers
+//! are floating point.
+
+use std::f64::consts::PI;
+
+/// The result of an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Number {
+    Scalar(f64),
+    Vector([f64; 3]),
+}
+
+/// Pieces of an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Number(f64),
+    /// Functions and constants, like `sin` or `pi`.
+    Name(&'a str),
+    /// Macros, like `$radius` or `${../radius}`.
+    Reference(&'a str),
+    Operator(&'static str),
+    Open,
+    Close,
+    Comma,
+}
+
+/// Operators, with the longer ones first so `<=` is not read as `<` followed by `=`.
+const OPERATORS: &[&str] = &[
+    "&&", "||", "<=", ">=", "==", "!=", "+", "-", "*", "/", "%", "&", "^", "<",
+    ">", "!", "?", ":",
+];
+
+/// Evaluate an expression. Macros are replaced by the values `lookup` finds for them; errors from
+/// `lookup` are returned as they are, while problems with the expression itself are described
+/// by `invalid`.
+pub(crate) fn evaluate<'a, E>(
+    expression: &'a str,
+    lookup: impl FnMut(&'a str) -> Result<Number, E>,
+    invalid: impl Fn(String) -> E,
+) -> Result<Number, E> {
+    let tokens = tokenize(expression).map_err(&invalid)?;
+    let mut evaluator = Evaluator {
+        tokens,
+        position: 0,
+        lookup,
+        invalid: &invalid,
+    };
+    let result = evaluator.ternary()?;
+    match evaluator.peek() {
+        None => Ok(result),
+        Some(token) => Err(invalid(format!("unexpected {}", describe(token)))),
+    }
+}
+
+/// Write a number the way it should appear in the content. Very large and very small numbers
+/// use the exponent notation.
+pub(crate) fn format(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-5..1e16).contains(&magnitude) {
+        format!("{:e}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Break the expression into tokens.
+fn tokenize(expression: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = expression;
+    while let Some(char) = rest.chars().next() {
+        let size = match char {
+            _ if char.is_whitespace() => char.len_utf8(),
+            '(' => {
+                tokens.push(Token::Open);
+                1
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                1
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                1
+            }
+            '0'..='9' | '.' => {
+                let size = number_size(rest);
+                let number = rest[..size].parse().map_err(|_| {
+                    format!("invalid number {:?}", &rest[..size])
+                })?;
+                tokens.push(Token::Number(number));
+                size
+            }
+            '$' => {
+                let size = reference_size(rest).ok_or_else(|| {
+                    format!(
+                        "invalid macro in {:?} (macros with more than a name must use braces, like `${{../a}}`)",
+                        rest
+                    )
+                })?;
+                tokens.push(Token::Reference(&rest[..size]));
+                size
+            }
+            _ if char.is_ascii_alphabetic() || char == '_' => {
+                let size = name_size(rest);
+                tokens.push(Token::Name(&rest[..size]));
+                size
+            }
+            _ => {
+                let operator = OPERATORS
+                    .iter()
+                    .find(|operator| rest.starts_with(*operator))
+                    .ok_or_else(|| format!("invalid character {:?}", char))?;
+                tokens.push(Token::Operator(operator));
+                operator.len()
+            }
+        };
+        rest = &rest[size..];
+    }
+    Ok(tokens)
+}
+
+/// Size of the number at the start of the content, like `1`, `.5` or `1.5e-3`.
+fn number_size(content: &str) -> usize {
+    let bytes = content.as_bytes();
+    let digits = |start: usize| {
+        start
+            + bytes[start..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count()
+    };
+
+    let mut size = digits(0);
+    if bytes.get(size) == Some(&b'.') {
+        size = digits(size + 1);
+    }
+    if matches!(bytes.get(size), Some(b'e' | b'E')) {
+        let mut exponent = size + 1;
+        if matches!(bytes.get(exponent), Some(b'+' | b'-')) {
+            exponent += 1;
+        }
+        if bytes.get(exponent).is_some_and(u8::is_ascii_digit) {
+            size = digits(exponent);
+        }
+    }
+    size
+}
+
+/// Size of the macro at the start of the content. Simple macros (`$radius`) only have letters,
+/// numbers and `_`; anything else (like scopes) must be inside braces (`${../radius}`).
+fn reference_size(content: &str) -> Option<usize> {
+    let name = &content[1..];
+    if name.starts_with('{') {
+        let mut depth = 0;
+        for (pos, char) in name.char_indices() {
+            match char {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(pos + 2);
+                    }
+                }
+                _ => {}
+            }
+        }
+        return None;
+    }
+
+    let size = name
+        .chars()
+        .take_while(|char| char.is_ascii_alphanumeric() || *char == '_')
+        .count();
+    (size > 0).then_some(size + 1)
+}
+
+/// Size of the name at the start of the content. Names can have C++ namespaces, like
+/// `Foam::sin` or `constant::mathematical::pi`.
+fn name_size(content: &str) -> usize {
+    let mut size = 0;
+    loop {
+        size += content[size..]
+            .chars()
+            .take_while(|char| char.is_ascii_alphanumeric() || *char == '_')
+            .count();
+        if content[size..].starts_with("::") {
+            size += 2;
+        } else {
+            return size;
+        }
+    }
+}
+
+/// How a token appears in error messages.
+fn describe(token: Token) -> String {
+    match token {
+        Token::Number(number) => format!("number {}", number),
+        Token::Name(name) => format!("name {:?}", name),
+        Token::Reference(reference) => format!("macro {:?}", reference),
+        Token::Operator(operator) => format!("operator {:?}", operator),
+        Token::Open => "\"(\"".to_string(),
+        Token::Close => "\")\"".to_string(),
+        Token::Comma => "\",\"".to_string(),
+    }
+}
+
+/// A recursive descent evaluator, following the C++ operator precedence.
+struct Evaluator<'a, 'i, L, I> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    lookup: L,
+    invalid: &'i I,
+}
+
+impl<'a, 'i, E, L, I> Evaluator<'a, 'i, L, I>
+where
+    L: FnMut(&'a str) -> Result<Number, E>,
+    I: Fn(String) -> E,
+{
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    /// Consume the operator, if it's the next token.
+    fn accept(&mut self, operator: &'static str) -> bool {
+        if self.peek() == Some(Token::Operator(operator)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consume the next token, which must be `expected`.
+    fn expect(&mut self, expected: Token<'a>) -> Result<(), E> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(self.error(format!(
+                "expected {}, found {}",
+                describe(expected),
+                describe(token)
+            ))),
+            None => Err(self.error(format!(
+                "expected {}, found the end of the expression",
+                describe(expected)
+            ))),
+        }
+    }
+
+    fn error(&self, reason: String) -> E {
+        (self.invalid)(reason)
+    }
+
+    /// `condition ? a : b`
+    fn ternary(&mut self) -> Result<Number, E> {
+        let condition = self.or()?;
+        if !self.accept("?") {
+            return Ok(condition);
+        }
+        let when_true = self.ternary()?;
+        self.expect(Token::Operator(":"))?;
+        let when_false = self.ternary()?;
+        Ok(if self.truth(condition)? {
+            when_true
+        } else {
+            when_false
+        })
+    }
+
+    fn or(&mut self) -> Result<Number, E> {
+        let mut left = self.and()?;
+        while self.accept("||") {
+            let right = self.and()?;
+            left = boolean(self.truth(left)? || self.truth(right)?);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Number, E> {
+        let mut left = self.cross()?;
+        while self.accept("&&") {
+            let right = self.cross()?;
+            left = boolean(self.truth(left)? && self.truth(right)?);
+        }
+        Ok(left)
+    }
+
+    /// `a ^ b`, the cross product. Like the bitwise xor it overloads in C++, it comes after the
+    /// comparisons.
+    fn cross(&mut self) -> Result<Number, E> {
+        let mut left = self.dot()?;
+        while self.accept("^") {
+            let right = self.dot()?;
+            left = match (left, right) {
+                (Number::Vector(a), Number::Vector(b)) => Number::Vector([
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ]),
+                _ => return Err(self.mismatch("^", left, right)),
+            };
+        }
+        Ok(left)
+    }
+
+    /// `a & b`, the dot product. Like the bitwise and it overloads in C++, it comes after the
+    /// comparisons.
+    fn dot(&mut self) -> Result<Number, E> {
+        let mut left = self.equality()?;
+        while self.accept("&") {
+            let right = self.equality()?;
+            left = match (left, right) {
+                (Number::Vector(a), Number::Vector(b)) => {
+                    Number::Scalar((0..3).map(|i| a[i] * b[i]).sum())
+                }
+                _ => return Err(self.mismatch("&", left, right)),
+            };
+        }
+        Ok(left)
+    }
+
+    fn equality(&mut self) -> Result<Number, E> {
+        let mut left = self.relational()?;
+        loop {
+            let Some(Token::Operator(operator @ ("==" | "!="))) = self.peek()
+            else {
+                return Ok(left);
+            };
+            self.position += 1;
+            let right = self.relational()?;
+            left = boolean((left == right) == (operator == "=="));
+        }
+    }
+
+    fn relational(&mut self) -> Result<Number, E> {
+        let mut left = self.sum()?;
+        loop {
+            let Some(Token::Operator(operator @ ("<" | "<=" | ">" | ">="))) =
+                self.peek()
+            else {
+                return Ok(left);
+            };
+            self.position += 1;
+            let right = self.sum()?;
+            left = match (left, right) {
+                (Number::Scalar(left), Number::Scalar(right)) => {
+                    boolean(match operator {
+                        "<" => left < right,
+                        "<=" => left <= right,
+                        ">" => left > right,
+                        _ => left >= right,
+                    })
+                }
+                _ => {
+                    return Err(self.error(format!(
+                        "vectors can't be compared with {:?}",
+                        operator
+                    )))
+                }
+            };
+        }
+    }
+
+    fn sum(&mut self) -> Result<Number, E> {
+        let mut left = self.product()?;
+        loop {
+            let Some(Token::Operator(operator @ ("+" | "-"))) = self.peek()
+            else {
+                return Ok(left);
+            };
+            self.position += 1;
+            let right = self.product()?;
+            left = match (left, right) {
+                (Number::Scalar(left), Number::Scalar(right)) => {
+                    Number::Scalar(if operator == "+" {
+                        left + right
+                    } else {
+                        left - right
+                    })
+                }
+                (Number::Vector(left), Number::Vector(right)) => {
+                    Number::Vector(std::array::from_fn(|i| {
+                        if operator == "+" {
+                            left[i] + right[i]
+                        } else {
+                            left[i] - right[i]
+                        }
+                    }))
+                }
+                _ => {
+                    return Err(self.error(format!(
+                        "can't use {:?} with a scalar and a vector",
+                        operator
+                    )))
+                }
+            };
+        }
+    }
+
+    fn product(&mut self) -> Result<Number, E> {
+        let mut left = self.unary()?;
+        loop {
+            let Some(Token::Operator(operator @ ("*" | "/" | "%"))) =
+                self.peek()
+            else {
+                return Ok(left);
+            };
+            self.position += 1;
+            let right = self.unary()?;
+            left = match (operator, left, right) {
+                ("*", Number::Scalar(left), Number::Scalar(right)) => {
+                    Number::Scalar(left * right)
+                }
+                ("*", Number::Scalar(scalar), Number::Vector(vector))
+                | ("*", Number::Vector(vector), Number::Scalar(scalar)) => {
+                    Number::Vector(vector.map(|component| component * scalar))
+                }
+                ("/", Number::Scalar(left), Number::Scalar(right)) => {
+                    Number::Scalar(left / right)
+                }
+                ("/", Number::Vector(vector), Number::Scalar(scalar)) => {
+                    Number::Vector(vector.map(|component| component / scalar))
+                }
+                ("%", Number::Scalar(left), Number::Scalar(right)) => {
+                    Number::Scalar(left % right)
+                }
+                _ => return Err(self.mismatch(operator, left, right)),
+            };
+        }
+    }
+
+    fn unary(&mut self) -> Result<Number, E> {
+        if self.accept("-") {
+            return Ok(match self.unary()? {
+                Number::Scalar(value) => Number::Scalar(-value),
+                Number::Vector(vector) => Number::Vector(vector.map(|c| -c)),
+            });
+        }
+        if self.accept("+") {
+            return self.unary();
+        }
+        if self.accept("!") {
+            let value = self.unary()?;
+            return Ok(boolean(!self.truth(value)?));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Number, E> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Number::Scalar(number)),
+            Some(Token::Reference(reference)) => (self.lookup)(reference),
+            Some(Token::Open) => {
+                let value = self.ternary()?;
+                self.expect(Token::Close)?;
+                Ok(value)
+            }
+            Some(Token::Name(name)) => {
+                let name = name.strip_prefix("Foam::").unwrap_or(name);
+                let name = name
+                    .strip_prefix("constant::mathematical::")
+                    .unwrap_or(name);
+                if self.peek() == Some(Token::Open) {
+                    self.position += 1;
+                    let arguments = self.arguments()?;
+                    self.function(name, &arguments)
+                } else {
+                    self.constant(name)
+                }
+            }
+            Some(token) => {
+                Err(self.error(format!("unexpected {}", describe(token))))
+            }
+            None => Err(self.error("unexpected end of the expression".into())),
+        }
+    }
+
+    /// The arguments of a function, after the `(`.
+    fn arguments(&mut self) -> Result<Vec<Number>, E> {
+        let mut arguments = Vec::new();
+        if self.peek() == Some(Token::Close) {
+            self.position += 1;
+            return Ok(arguments);
+        }
+        loop {
+            arguments.push(self.ternary()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::Close) => return Ok(arguments),
+                Some(token) => {
+                    return Err(self.error(format!(
+                        "expected \",\" or \")\", found {}",
+                        describe(token)
+                    )))
+                }
+                None => {
+                    return Err(self.error(
+                        "function arguments started but never closed".into(),
+                    ))
+                }
+            }
+        }
+    }
+
+    fn constant(&self, name: &str) -> Result<Number, E> {
+        match name {
+            "pi" => Ok(Number::Scalar(PI)),
+            "true" => Ok(boolean(true)),
+            "false" => Ok(boolean(false)),
+            _ => Err(self.error(format!("unknown name {:?}", name))),
+        }
+    }
+
+    fn function(&self, name: &str, arguments: &[Number]) -> Result<Number, E> {
+        use Number::Scalar;
+        use Number::Vector;
+
+        let scalar = |function: fn(f64) -> f64| match arguments {
+            [Scalar(value)] => Ok(Scalar(function(*value))),
+            _ => Err(self.error(format!("{} expects a single scalar", name))),
+        };
+        let scalars = |function: fn(f64, f64) -> f64| match arguments {
+            [Scalar(a), Scalar(b)] => Ok(Scalar(function(*a, *b))),
+            _ => Err(self.error(format!("{} expects two scalars", name))),
+        };
+
+        match name {
+            "pi" if arguments.is_empty() => Ok(Scalar(PI)),
+            "sin" => scalar(f64::sin),
+            "cos" => scalar(f64::cos),
+            "tan" => scalar(f64::tan),
+            "asin" => scalar(f64::asin),
+            "acos" => scalar(f64::acos),
+            "atan" => scalar(f64::atan),
+            "sinh" => scalar(f64::sinh),
+            "cosh" => scalar(f64::cosh),
+            "tanh" => scalar(f64::tanh),
+            "sqrt" => scalar(f64::sqrt),
+            "cbrt" => scalar(f64::cbrt),
+            "exp" => scalar(f64::exp),
+            "log" => scalar(f64::ln),
+            "log10" => scalar(f64::log10),
+            "sqr" => scalar(|value| value * value),
+            "floor" => scalar(f64::floor),
+            "ceil" => scalar(f64::ceil),
+            "round" => scalar(f64::round),
+            "sign" => scalar(|value| if value < 0.0 { -1.0 } else { 1.0 }),
+            "degToRad" if arguments.is_empty() => Ok(Scalar(PI / 180.0)),
+            "degToRad" => scalar(f64::to_radians),
+            "radToDeg" if arguments.is_empty() => Ok(Scalar(180.0 / PI)),
+            "radToDeg" => scalar(f64::to_degrees),
+            "pow" => scalars(f64::powf),
+            "atan2" => scalars(f64::atan2),
+            "hypot" => scalars(f64::hypot),
+            "min" => scalars(f64::min),
+            "max" => scalars(f64::max),
+            "mag" | "abs" => match arguments {
+                [Scalar(value)] => Ok(Scalar(value.abs())),
+                [Vector(vector)] if name == "mag" => {
+                    Ok(Scalar(vector.iter().map(|c| c * c).sum::<f64>().sqrt()))
+                }
+                _ => {
+                    Err(self.error(format!("{} expects a single value", name)))
+                }
+            },
+            "magSqr" => match arguments {
+                [Scalar(value)] => Ok(Scalar(value * value)),
+                [Vector(vector)] => {
+                    Ok(Scalar(vector.iter().map(|c| c * c).sum()))
+                }
+                _ => {
+                    Err(self.error(format!("{} expects a single value", name)))
+                }
+            },
+            "vector" => match arguments {
+                [Scalar(x), Scalar(y), Scalar(z)] => Ok(Vector([*x, *y, *z])),
+                _ => Err(self.error("vector expects three scalars".into())),
+            },
+            _ => Err(self.error(format!("unknown function {:?}", name))),
+        }
+    }
+
+    /// An operator that can't be used with these operands.
+    fn mismatch(&self, operator: &str, left: Number, right: Number) -> E {
+        self.error(format!(
+            "can't use {:?} with {} and {}",
+            operator,
+            kind(left),
+            kind(right)
+        ))
+    }
+
+    /// Use a value as condition. Like C++, any scalar other than zero is true.
+    fn truth(&self, value: Number) -> Result<bool, E> {
+        match value {
+            Number::Scalar(value) => Ok(value != 0.0),
+            Number::Vector(_) => {
+                Err(self.error("vectors can't be used as conditions".into()))
+            }
+        }
+    }
+}
+
+/// Like C++, booleans are numbers.
+fn boolean(value: bool) -> Number {
+    Number::Scalar(if value { 1.0 } else { 0.0 })
+}
+
+/// How a value appears in error messages.
+fn kind(value: Number) -> &'static str {
+    match value {
+        Number::Scalar(_) => "a scalar",
+        Number::Vector(_) => "a vector",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn eval(expression: &str) -> Result<Number, String> {
+        evaluate(
+            expression,
+            |reference| match reference {
+                "$radius" => Ok(Number::Scalar(0.5)),
+                "${../v}" => Ok(Number::Vector([1.0, 2.0, 3.0])),
+                _ => Err(format!("unresolved {}", reference)),
+            },
+            |reason| reason,
+        )
+    }
+
+    fn scalar(expression: &str) -> f64 {
+        match eval(expression) {
+            Ok(Number::Scalar(value)) => value,
+            other => panic!("{:?} is not a scalar: {:?}", expression, other),
+        }
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(scalar("1 + 2 * 3"), 7.0);
+        assert_eq!(scalar("(1 + 2) * 3"), 9.0);
+        assert_eq!(scalar("10 - 4 - 3"), 3.0);
+        assert_eq!(scalar("1/4"), 0.25);
+        assert_eq!(scalar("7 % 4"), 3.0);
+        assert_eq!(scalar("-2 * -3"), 6.0);
+        assert_eq!(scalar("1.5e2 + .5"), 150.5);
+    }
+
+    #[test]
+    fn comparisons() {
+        assert_eq!(scalar("1 < 2"), 1.0);
+        assert_eq!(scalar("2 <= 1"), 0.0);
+        assert_eq!(scalar("1 == 1 && 2 != 2"), 0.0);
+        assert_eq!(scalar("1 > 2 || !0"), 1.0);
+        assert_eq!(scalar("$radius > 1 ? 10 : 20"), 20.0);
+    }
+
+    #[test]
+    fn functions() {
+        assert_eq!(scalar("degToRad(180)"), PI);
+        assert_eq!(scalar("radToDeg(pi)"), 180.0);
+        assert_eq!(scalar("sqrt(16)"), 4.0);
+        assert_eq!(scalar("pow(2, 10)"), 1024.0);
+        assert_eq!(scalar("Foam::sqr(3)"), 9.0);
+        assert_eq!(scalar("constant::mathematical::pi"), PI);
+        assert!((scalar("$radius*sin(degToRad(30))") - 0.25).abs() < 1e-12);
+        assert!((scalar("cos(0)") - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn vectors() {
+        assert_eq!(
+            eval("${../v} * 2 + vector(1, 0, 0)"),
+            Ok(Number::Vector([3.0, 4.0, 6.0]))
+        );
+        assert_eq!(scalar("mag(vector(3, 4, 0))"), 5.0);
+        assert_eq!(scalar("${../v} & vector(1, 1, 1)"), 6.0);
+        assert_eq!(
+            eval("vector(1, 0, 0) ^ vector(0, 1, 0)"),
+            Ok(Number::Vector([0.0, 0.0, 1.0]))
+        );
+        assert_eq!(
+            eval("vector(1, 0, 0) + 1"),
+            Err("can't use \"+\" with a scalar and a vector".to_string())
+        );
+    }
+
+    #[test]
+    fn precedence() {
+        // Like C++, `&` and `^` come after the sums and the comparisons.
+        assert_eq!(
+            scalar("vector(1, 0, 0) + vector(0, 1, 0) & vector(1, 1, 0)"),
+            2.0
+        );
+        assert_eq!(
+            eval("vector(1, 0, 0) ^ vector(0, 1, 0) + vector(0, 0, 1)"),
+            Ok(Number::Vector([0.0, -1.0, 1.0]))
+        );
+        assert_eq!(
+            eval("2 + 4 & 1"),
+            Err("can't use \"&\" with a scalar and a scalar".to_string())
+        );
+        // `&` comes before `^`.
+        assert_eq!(
+            eval("vector(1, 0, 0) ^ vector(0, 1, 0) & vector(0, 0, 1)"),
+            Err("can't use \"^\" with a vector and a scalar".to_string())
+        );
+        // Equality comes after the other comparisons.
+        assert_eq!(scalar("1 < 2 == 2 < 3"), 1.0);
+        assert_eq!(scalar("0 == 1 < 2"), 0.0);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(eval("$missing"), Err("unresolved $missing".to_string()));
+        assert_eq!(
+            eval("1 +"),
+            Err("unexpected end of the expression".to_string())
+        );
+        assert_eq!(
+            eval("(1"),
+            Err("expected \")\", found the end of the expression".to_string())
+        );
+        assert_eq!(eval("1 2"), Err("unexpected number 2".to_string()));
+        assert_eq!(eval("foo(1)"), Err("unknown function \"foo\"".to_string()));
+        assert_eq!(eval("1 @ 2"), Err("invalid character '@'".to_string()));
+        assert_eq!(
+            eval("$../a + 1"),
+            Err("invalid macro in \"$../a + 1\" (macros with more than a name must use braces, like `${../a}`)".to_string())
+        );
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format(45.0), "45");
+        assert_eq!(format(0.25), "0.25");
+        assert_eq!(format(-1.5e-10), "-1.5e-10");
+        assert_eq!(format(2e20), "2e20");
+    }
+}
@@ -0,0 +1,188 @@
+//! Navigation over the tree, keeping track of where we are.
+
+use crate::Cursor;
+use crate::Dictionary;
+use crate::Foam;
+use crate::FoamError;
+
+impl<'a> Foam<'a> {
+    /// Start a [`Cursor`] at this dictionary, which is considered the root.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("nCorrectors 2; PIMPLE { momentumPredictor yes; }").unwrap();
+    /// let pimple = root.cursor().unwrap().child("PIMPLE").unwrap();
+    /// assert_eq!(pimple.path(), ["PIMPLE"]);
+    /// assert!(pimple.lookup("nCorrectors").is_none());
+    /// assert_eq!(
+    ///     pimple.lookup_recursive("nCorrectors"),
+    ///     Some([Foam::Value("2")].as_slice())
+    /// );
+    /// ```
+    ///
+    /// Only dictionaries can have cursors.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("a 1;").unwrap();
+    /// let a = root.get_first("a").unwrap();
+    /// assert_eq!(a.cursor(), Err(FoamError::NotADictionary));
+    /// ```
+    pub fn cursor(&self) -> Result<Cursor<'_, 'a>, FoamError<'a>> {
+        match self {
+            Foam::Dictionary(dict) => Ok(dict.cursor()),
+            _ => Err(FoamError::NotADictionary),
+        }
+    }
+}
+
+impl<'a> Dictionary<'a> {
+    /// Start a [`Cursor`] at this dictionary, which is considered the root.
+    pub fn cursor(&self) -> Cursor<'_, 'a> {
+        Cursor {
+            scopes: vec![self],
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<'t, 'a> Cursor<'t, 'a> {
+    /// The current dictionary.
+    pub fn dict(&self) -> &'t Dictionary<'a> {
+        self.scopes.last().expect("the root is always there")
+    }
+
+    /// The keys leading from the root to the current dictionary, as they are in the tree (for
+    /// dictionaries found by a regular expression key, the expression).
+    pub fn path(&self) -> &[&'a str] {
+        &self.path
+    }
+
+    /// Check if the cursor is at the root.
+    pub fn is_root(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// A cursor at the dictionary around the current one, if this is not the root.
+    pub fn parent(&self) -> Option<Cursor<'t, 'a>> {
+        if self.is_root() {
+            return None;
+        }
+        let mut parent = self.clone();
+        parent.scopes.pop();
+        parent.path.pop();
+        Some(parent)
+    }
+
+    /// A cursor at the root.
+    pub fn root(&self) -> Cursor<'t, 'a> {
+        self.scopes[0].cursor()
+    }
+
+    /// A cursor at a dictionary inside the current one. Like [`Dictionary::lookup`], if no key
+    /// matches exactly, regular expression keys are used.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("solvers { \"(U|k)\" { solver PBiCGStab; } } p 1;").unwrap();
+    /// let cursor = root.cursor().unwrap();
+    /// let k = cursor.child("solvers").unwrap().child("k").unwrap();
+    /// assert_eq!(k.path(), ["solvers", "(U|k)"]);
+    /// assert_eq!(cursor.child("missing"), Err(FoamError::NoSuchKey));
+    /// assert_eq!(cursor.child("p"), Err(FoamError::NotADictionary));
+    /// ```
+    pub fn child(&self, key: &str) -> Result<Cursor<'t, 'a>, FoamError<'a>> {
+        let entry =
+            self.dict().lookup_entry(key).ok_or(FoamError::NoSuchKey)?;
+        let dict = entry
+            .values
+            .iter()
+            .find_map(|value| match value {
+                Foam::Dictionary(dict) => Some(dict),
+                _ => None,
+            })
+            .ok_or(FoamError::NotADictionary)?;
+
+        let mut child = self.clone();
+        child.scopes.push(dict);
+        child.path.push(entry.key);
+        Ok(child)
+    }
+
+    /// Retrieve the values of a key in the current dictionary (see [`Dictionary::lookup`]).
+    pub fn lookup(&self, key: &str) -> Option<&'t [Foam<'a>]> {
+        self.dict().lookup(key)
+    }
+
+    /// Retrieve the values of a key in the current dictionary or, if it's not there, in the
+    /// closest dictionary around it that has it.
+    pub fn lookup_recursive(&self, key: &str) -> Option<&'t [Foam<'a>]> {
+        self.scope_of(key).and_then(|scope| scope.lookup(key))
+    }
+
+    /// A cursor at the dictionary [`Cursor::lookup_recursive`] would find the key in.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("a 1; b { c { d 2; } }").unwrap();
+    /// let c = root.cursor().unwrap().child("b").unwrap().child("c").unwrap();
+    /// assert_eq!(c.scope_of("d").unwrap().path(), ["b", "c"]);
+    /// assert!(c.scope_of("a").unwrap().is_root());
+    /// assert!(c.scope_of("e").is_none());
+    /// ```
+    pub fn scope_of(&self, key: &str) -> Option<Cursor<'t, 'a>> {
+        let mut scope = self.clone();
+        loop {
+            if scope.lookup(key).is_some() {
+                return Some(scope);
+            }
+            scope = scope.parent()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Foam;
+    use crate::FoamError;
+
+    #[test]
+    fn recursive_lookup() {
+        let root = Foam::parse(
+            "tolerance 1e-6;
+            solvers { p { solver PCG; } \"(U|k)\" { solver smoothSolver; tolerance 1e-5; } }",
+        )
+        .unwrap();
+        let solvers = root.cursor().unwrap().child("solvers").unwrap();
+        let p = solvers.child("p").unwrap();
+        let u = solvers.child("U").unwrap();
+
+        assert_eq!(
+            p.lookup_recursive("tolerance"),
+            Some([Foam::Value("1e-6")].as_slice())
+        );
+        assert_eq!(
+            u.lookup_recursive("tolerance"),
+            Some([Foam::Value("1e-5")].as_slice())
+        );
+        assert_eq!(u.path(), ["solvers", "(U|k)"]);
+        assert_eq!(p.lookup_recursive("missing"), None);
+    }
+
+    #[test]
+    fn navigation() {
+        let root = Foam::parse("a { b { c 1; } }").unwrap();
+        let cursor = root.cursor().unwrap();
+        assert!(cursor.is_root());
+        assert_eq!(cursor.parent(), None);
+
+        let b = cursor.child("a").unwrap().child("b").unwrap();
+        assert_eq!(b.path(), ["a", "b"]);
+        assert_eq!(b.parent().unwrap().path(), ["a"]);
+        assert_eq!(b.root(), cursor);
+        assert_eq!(b.dict().get("c"), Some([Foam::Value("1")].as_slice()));
+        assert_eq!(b.child("c"), Err(FoamError::NotADictionary));
+    }
+}
@@ -0,0 +1,344 @@
+//! Converts errors to something people can read.
+
+use std::fmt::Display;
+
+use crate::Diagnostic;
+use crate::FoamError;
+use crate::Location;
+
+impl<'a> FoamError<'a> {
+    /// Where, in the content, the error happened. Errors from accessing elements in the tree
+    /// don't have a location.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let error = Foam::parse("var value\n  @").unwrap_err();
+    /// let location = error.location().unwrap();
+    /// assert_eq!((location.line, location.column), (2, 3));
+    /// ```
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            FoamError::EndOfContent { location }
+            | FoamError::UnterminatedComment { location }
+            | FoamError::UnterminatedVerbatim { location }
+            | FoamError::UnterminatedConditional { location }
+            | FoamError::InvalidCharacter { location, .. }
+            | FoamError::NoDictValues { location, .. }
+            | FoamError::InvalidDictEnd { location, .. }
+            | FoamError::MissingKeyword { location, .. }
+            | FoamError::UnexpectedToken { location, .. } => Some(location),
+            FoamError::UnresolvedReference { location, .. }
+            | FoamError::InvalidMerge { location, .. }
+            | FoamError::CyclicReference { location, .. }
+            | FoamError::IncludeNotFound { location, .. }
+            | FoamError::IncludeFailed { location, .. }
+            | FoamError::IncludeCycle { location, .. }
+            | FoamError::InvalidDirective { location, .. }
+            | FoamError::InvalidPattern { location, .. }
+            | FoamError::InvalidExpression { location, .. }
+            | FoamError::InInclude { location, .. }
+            | FoamError::MissingDimensions { location, .. }
+            | FoamError::InvalidDimensions { location, .. }
+            | FoamError::InvalidDimensionedValue { location, .. } => {
+                location.as_ref()
+            }
+            FoamError::InvalidPath { .. }
+            | FoamError::InvalidNumber { .. }
+            | FoamError::InvalidLabel { .. }
+            | FoamError::InvalidSwitch { .. }
+            | FoamError::InvalidWord { .. }
+            | FoamError::NotADictionary
+            | FoamError::NotAValue
+            | FoamError::NotAList
+            | FoamError::ComponentCount { .. }
+            | FoamError::InvalidComponent { .. }
+            | FoamError::NoSuchKey
+            | FoamError::NoSuchValue => None,
+        }
+    }
+
+    /// A suggestion on how to fix the error.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            FoamError::EndOfContent { .. } => {
+                Some("a list, dictionary or dimension was opened but never closed")
+            }
+            FoamError::UnterminatedComment { .. } => {
+                Some("block comments must be closed with `*/`")
+            }
+            FoamError::UnterminatedVerbatim { .. } => {
+                Some("verbatim blocks must be closed with `#}`")
+            }
+            FoamError::UnterminatedConditional { .. } => {
+                Some("`#if` and `#ifeq` blocks must be closed with `#endif`")
+            }
+            FoamError::InvalidCharacter { .. } => Some(
+                "if this is part of a value, wrap the value in double quotes",
+            ),
+            FoamError::NoDictValues { .. } => {
+                Some("dictionaries need at least one entry, like `name value;`")
+            }
+            FoamError::InvalidDictEnd { .. } => {
+                Some("dictionaries must be closed with `}`")
+            }
+            FoamError::MissingKeyword { .. } => {
+                Some("entries start with a keyword, like `name value;`")
+            }
+            FoamError::UnexpectedToken { .. } => Some(
+                "check for a missing `;` or unbalanced brackets before this point",
+            ),
+            FoamError::UnresolvedReference { .. } => Some(
+                "references can only use entries defined before them",
+            ),
+            FoamError::InvalidMerge { .. } => Some(
+                "only dictionaries can be merged; use `key $value;` for other entries",
+            ),
+            FoamError::CyclicReference { .. } => {
+                Some("an entry can't use its own value")
+            }
+            FoamError::IncludeNotFound { .. } => Some(
+                "use `#includeIfPresent` if the file is not always there",
+            ),
+            FoamError::IncludeFailed { .. } => None,
+            FoamError::IncludeCycle { .. } => {
+                Some("a file can't include itself, even indirectly")
+            }
+            FoamError::InvalidDirective { .. } => Some(
+                "includes take a file name, like `#include \"file\"`, and `#remove` takes keys or patterns, like `#remove (a \"b.*\")`",
+            ),
+            FoamError::InvalidPattern { .. } => {
+                Some("quoted keys are regular expressions; check for unbalanced brackets")
+            }
+            FoamError::InvalidExpression { .. } => Some(
+                "check the syntax of the expression and the names of the functions it uses",
+            ),
+            FoamError::InInclude { error, .. } => error.hint(),
+            FoamError::MissingDimensions { .. }
+            | FoamError::InvalidDimensionedValue { .. } => Some(
+                "dimensioned entries are written like `nu [0 2 -1 0 0 0 0] 1e-05;`",
+            ),
+            FoamError::InvalidDimensions { .. } => Some(
+                "dimensions are 5 or 7 exponents, like `[0 2 -1 0 0 0 0]`, or SI units, like `[m^2 s^-1]`, `[m/s]` or `[Pa]`",
+            ),
+            FoamError::InvalidLabel { .. } => {
+                Some("integers can't have a decimal point or an exponent")
+            }
+            FoamError::InvalidSwitch { .. } => Some(
+                "switches are one of `true`, `false`, `yes`, `no`, `on`, `off`, `y`, `n`, `t`, `f`, `any` or `none`",
+            ),
+            FoamError::InvalidWord { .. } => {
+                Some("words can't be quoted or contain spaces")
+            }
+            FoamError::ComponentCount { .. } => Some(
+                "vectors have 3 components, tensors 9, symmetric tensors 6 and spherical tensors 1",
+            ),
+            FoamError::InvalidPath { .. }
+            | FoamError::InvalidNumber { .. }
+            | FoamError::NotADictionary
+            | FoamError::NotAValue
+            | FoamError::NotAList
+            | FoamError::InvalidComponent { .. }
+            | FoamError::NoSuchKey
+            | FoamError::NoSuchValue => None,
+        }
+    }
+
+    /// Attach the error to the content it came from, so it can be displayed to people.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let content = "solvers\n{\n    p ( 1 2 };\n}";
+    /// let error = Foam::parse(content).unwrap_err();
+    /// let diagnostic = error.diagnostic("system/fvSolution", content);
+    /// let expected = "\
+    /// error: Unexpected keyword \"}\" when processing list
+    ///  --> system/fvSolution:3:13
+    ///   |
+    /// 3 |     p ( 1 2 };
+    ///   |             ^
+    ///   = hint: check for a missing `;` or unbalanced brackets before this point
+    /// ";
+    /// assert_eq!(diagnostic.to_string(), expected);
+    /// ```
+    pub fn diagnostic(&self, name: &str, content: &str) -> Diagnostic {
+        let location = self.location().cloned();
+        let mut message = self.to_string();
+        let mut line = None;
+        if let Some(location) = &location {
+            // The location is part of the error message, but diagnostics display it on their own.
+            let suffix = format!(" (at {})", location);
+            if let Some(stripped) = message.strip_suffix(&suffix) {
+                message = stripped.to_string();
+            }
+            line = content
+                .lines()
+                .nth(location.line - 1)
+                .or(Some(""))
+                .map(|line| line.to_string());
+        }
+
+        Diagnostic {
+            name: name.to_string(),
+            message,
+            location,
+            line,
+            hint: self.hint(),
+            cause: None,
+        }
+    }
+}
+
+impl Diagnostic {
+    /// Add the diagnostic that caused this one.
+    pub fn caused_by(mut self, cause: Diagnostic) -> Self {
+        self.cause = Some(Box::new(cause));
+        self
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "error: {}", self.message)?;
+        let (Some(location), Some(line)) = (&self.location, &self.line) else {
+            writeln!(f, " --> {}", self.name)?;
+            if let Some(hint) = self.hint {
+                writeln!(f, "  = hint: {}", hint)?;
+            }
+            return Ok(());
+        };
+
+        let number = location.line.to_string();
+        let gutter = " ".repeat(number.len());
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter, self.name, location.line, location.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        if line.is_empty() {
+            writeln!(f, "{} |", number)?;
+        } else {
+            writeln!(f, "{} | {}", number, line)?;
+        }
+
+        // Keep tabs in the padding, so the marker lines up with the line above.
+        let padding: String = line
+            .chars()
+            .take(location.column - 1)
+            .map(|char| if char == '\t' { '\t' } else { ' ' })
+            .collect();
+        let available =
+            line.chars().count().saturating_sub(location.column - 1);
+        let width = location.span.len().min(available).max(1);
+        writeln!(f, "{} | {}{}", gutter, padding, "^".repeat(width))?;
+
+        if let Some(hint) = self.hint {
+            writeln!(f, "{} = hint: {}", gutter, hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Diagnostic {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::Foam;
+
+    #[test]
+    fn invalid_character() {
+        let content = "var1 value1;\nvar2 @value2;\n";
+        let error = Foam::parse(content).unwrap_err();
+        let diagnostic = error.diagnostic("controlDict", content);
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: Invalid character \"@\"
+ --> controlDict:2:6
+  |
+2 | var2 @value2;
+  |      ^
+  = hint: if this is part of a value, wrap the value in double quotes
+"
+        );
+    }
+
+    #[test]
+    fn long_span_and_tabs() {
+        let content = "var\t/* never closed\nmore";
+        let error = Foam::parse(content).unwrap_err();
+        let diagnostic = error.diagnostic("fvSchemes", content);
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: Comment started but never closed
+ --> fvSchemes:1:5
+  |
+1 | var\t/* never closed
+  |    \t^^^^^^^^^^^^^^^
+  = hint: block comments must be closed with `*/`
+"
+        );
+    }
+
+    #[test]
+    fn end_of_content() {
+        let content = "var ( 1 2\n";
+        let error = Foam::parse(content).unwrap_err();
+        let diagnostic = error.diagnostic("blockMeshDict", content);
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: Unexpected end of content
+ --> blockMeshDict:2:1
+  |
+2 |
+  | ^
+  = hint: a list, dictionary or dimension was opened but never closed
+"
+        );
+    }
+
+    #[test]
+    fn without_location() {
+        let root = Foam::parse("var value;").unwrap();
+        let error = root.get("other").unwrap_err();
+        let diagnostic = error.diagnostic("controlDict", "var value;");
+        assert_eq!(
+            diagnostic.to_string(),
+            "error: The requested key does not exist\n --> controlDict\n"
+        );
+    }
+
+    #[test]
+    fn expression_hint() {
+        let content = "a #calc \"1 +\";";
+        let error = Foam::parse(content).unwrap().expand().unwrap_err();
+        assert_eq!(
+            error.hint(),
+            Some("check the syntax of the expression and the names of the functions it uses")
+        );
+    }
+
+    #[test]
+    fn source_chain() {
+        let inner_content = "a @;";
+        let inner = Foam::parse(inner_content)
+            .unwrap_err()
+            .diagnostic("inner", inner_content);
+        let outer_content = "b ( 1";
+        let outer = Foam::parse(outer_content)
+            .unwrap_err()
+            .diagnostic("outer", outer_content)
+            .caused_by(inner.clone());
+
+        let source = outer.source().unwrap();
+        assert_eq!(source.to_string(), inner.to_string());
+        assert!(source.source().is_none());
+    }
+}
@@ -0,0 +1,623 @@
+//! Ordered dictionaries.
+
+use crate::Coded;
+use crate::Dictionary;
+use crate::Entry;
+use crate::Foam;
+use crate::FoamError;
+use crate::Location;
+
+impl<'a> Dictionary<'a> {
+    /// Create an empty dictionary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry to the end of the dictionary. If the key already exists, the previous entries
+    /// are kept, but the new one is the one that counts.
+    ///
+    /// If both the new and the previous values are dictionaries, the new entries are merged into
+    /// the previous dictionary instead, recursively, like OpenFOAM does.
+    ///
+    /// ```
+    /// # use foamparser::Dictionary;
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     solvers { p { solver GAMG; tolerance 1e-6; } U { solver PBiCG; } }
+    ///     solvers { p { tolerance 1e-8; } }
+    /// ").unwrap();
+    /// let solvers = root.get_first("solvers").unwrap();
+    /// let p = solvers.get_first("p").unwrap();
+    /// assert_eq!(p.get_first_value("solver"), Ok("GAMG"));
+    /// assert_eq!(p.get_first_value("tolerance"), Ok("1e-8"));
+    /// assert!(solvers.get("U").is_ok());
+    /// ```
+    ///
+    /// ```
+    /// # use foamparser::Dictionary;
+    /// # use foamparser::Foam;
+    /// let mut dict = Dictionary::new();
+    /// dict.insert("b", vec![Foam::Value("1")]);
+    /// dict.insert("a", vec![Foam::Value("2")]);
+    /// dict.insert("b", vec![Foam::Value("3")]);
+    /// assert_eq!(dict.keys().collect::<Vec<_>>(), vec!["b", "a"]);
+    /// assert_eq!(dict.get("b"), Some([Foam::Value("3")].as_slice()));
+    /// assert_eq!(dict.len(), 3);
+    /// ```
+    pub fn insert(&mut self, key: &'a str, values: Vec<Foam<'a>>) {
+        let entry = Entry {
+            key,
+            quoted: false,
+            values,
+            location: None,
+            merged_from: None,
+        };
+        self.add(entry, None);
+    }
+
+    /// Add an entry to the end of the dictionary. Quoted keys are regular expressions; the entry is
+    /// always added, but if its key can't be compiled, it is only found by its exact text, and the
+    /// returned [`FoamError::InvalidPattern`] says why.
+    pub(crate) fn push(&mut self, entry: Entry<'a>) -> Option<FoamError<'a>> {
+        let mut error = None;
+        let pattern = if entry.quoted {
+            key_pattern(entry.key)
+                .map_err(|reason| {
+                    error = Some(FoamError::InvalidPattern {
+                        pattern: entry.key,
+                        reason: reason.to_string(),
+                        location: entry.location.clone(),
+                    })
+                })
+                .ok()
+        } else {
+            None
+        };
+        self.add(entry, pattern);
+        error
+    }
+
+    /// Add an entry, with its key already compiled if it is a pattern.
+    fn add(&mut self, entry: Entry<'a>, pattern: Option<regex::Regex>) {
+        let position = self.entries.len();
+        if let Some(merged) = self.merge_with_previous(&entry) {
+            self.merged.insert(position, merged);
+        }
+        self.index.entry(entry.key).or_default().push(position);
+        if let Some(pattern) = pattern {
+            self.patterns.push((position, pattern));
+        }
+        self.entries.push(entry);
+    }
+
+    /// If the entry and the current one with the same key are both dictionaries, the entry with
+    /// the two dictionaries merged.
+    fn merge_with_previous(&self, entry: &Entry<'a>) -> Option<Entry<'a>> {
+        let [Foam::Dictionary(new)] = entry.values.as_slice() else {
+            return None;
+        };
+        let previous = self.get_entry(entry.key)?;
+        let [Foam::Dictionary(previous)] = previous.values.as_slice() else {
+            return None;
+        };
+
+        let mut merged = previous.clone();
+        for (position, inner) in new.entries.iter().enumerate() {
+            merged.add(inner.clone(), new.pattern_at(position));
+        }
+        Some(Entry {
+            key: entry.key,
+            quoted: entry.quoted,
+            values: vec![Foam::Dictionary(merged)],
+            location: entry.location.clone(),
+            merged_from: entry.merged_from,
+        })
+    }
+
+    /// The compiled pattern of the entry at `position`, if its key is a pattern.
+    fn pattern_at(&self, position: usize) -> Option<regex::Regex> {
+        self.patterns
+            .binary_search_by_key(&position, |(start, _)| *start)
+            .ok()
+            .map(|found| self.patterns[found].1.clone())
+    }
+
+    /// The entry at `position`, as lookups see it: with repeated dictionaries merged.
+    fn effective(&self, position: usize) -> &Entry<'a> {
+        self.merged
+            .get(&position)
+            .unwrap_or(&self.entries[position])
+    }
+
+    /// Keep only the entries `keep` returns `true` for, removing the others.
+    ///
+    /// ```
+    /// # use foamparser::Dictionary;
+    /// # use foamparser::Foam;
+    /// let mut dict = Dictionary::from([
+    ///     ("a", vec![Foam::Value("1")]),
+    ///     ("b", vec![Foam::Value("2")]),
+    ///     ("a", vec![Foam::Value("3")]),
+    /// ]);
+    /// dict.retain(|entry| entry.key != "a");
+    /// assert_eq!(dict, Dictionary::from([("b", vec![Foam::Value("2")])]));
+    /// ```
+    pub fn retain(&mut self, mut keep: impl FnMut(&Entry<'a>) -> bool) {
+        let entries = std::mem::take(&mut self.entries);
+        let mut patterns =
+            std::mem::take(&mut self.patterns).into_iter().peekable();
+        self.index.clear();
+        self.merged.clear();
+        for (position, entry) in entries.into_iter().enumerate() {
+            let pattern = patterns
+                .next_if(|(start, _)| *start == position)
+                .map(|(_, pattern)| pattern);
+            if keep(&entry) {
+                self.add(entry, pattern);
+            }
+        }
+    }
+
+    /// Retrieve the values of a key. If the key appears more than once, the values of the last
+    /// occurrence are returned.
+    pub fn get(&self, key: &str) -> Option<&[Foam<'a>]> {
+        self.get_entry(key).map(|entry| entry.values.as_slice())
+    }
+
+    /// Retrieve the entry of a key. Like [`Dictionary::get`], if the key appears more than once,
+    /// the last occurrence is returned (with the previous ones merged into it, if they are all
+    /// dictionaries).
+    pub fn get_entry(&self, key: &str) -> Option<&Entry<'a>> {
+        self.index
+            .get(key)
+            .and_then(|positions| positions.last())
+            .map(|&position| self.effective(position))
+    }
+
+    /// Retrieve the values of a key, like [`Dictionary::get`], but if no key matches exactly, use
+    /// the last quoted key that, as a regular expression, matches the whole key. This is how
+    /// OpenFOAM finds entries like `"(U|k|epsilon)Final"` or `"inlet.*"`. Quoted keys that can't be
+    /// compiled (like the ones with backreferences) only match their exact text.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     \"(U|k|epsilon)\" { solver smoothSolver; }
+    ///     \"(U|k|epsilon)Final\" { solver PBiCGStab; }
+    ///     kFinal { solver PCG; }
+    /// ").unwrap();
+    /// let solvers = root.as_dict().unwrap();
+    /// let u_final = solvers.lookup("UFinal").unwrap()[0].get_first_value("solver");
+    /// assert_eq!(u_final, Ok("PBiCGStab"));
+    /// let k_final = solvers.lookup("kFinal").unwrap()[0].get_first_value("solver");
+    /// assert_eq!(k_final, Ok("PCG"));
+    /// assert!(solvers.lookup("p").is_none());
+    /// ```
+    pub fn lookup(&self, key: &str) -> Option<&[Foam<'a>]> {
+        self.lookup_entry(key).map(|entry| entry.values.as_slice())
+    }
+
+    /// Retrieve the entry of a key, like [`Dictionary::lookup`]. The key of the entry tells if it
+    /// was found by a pattern.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("\"inlet.*\" { type fixedValue; } outlet { type zeroGradient; }").unwrap();
+    /// let boundary = root.as_dict().unwrap();
+    /// let entry = boundary.lookup_entry("inlet2").unwrap();
+    /// assert_eq!((entry.key, entry.quoted), ("inlet.*", true));
+    /// ```
+    pub fn lookup_entry(&self, key: &str) -> Option<&Entry<'a>> {
+        self.get_entry(key).or_else(|| {
+            self.patterns
+                .iter()
+                .rev()
+                .find(|(_, pattern)| pattern.is_match(key))
+                .map(|(position, _)| self.effective(*position))
+        })
+    }
+
+    /// List the entries with quoted keys that, as regular expressions, match the whole key, in
+    /// order. If no key matches exactly, the last one is the one [`Dictionary::lookup`] uses.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("\"(U|k)\" 1; \".*\" 2; \"p.*\" 3;").unwrap();
+    /// let dict = root.as_dict().unwrap();
+    /// let patterns = dict
+    ///     .matching_patterns("k")
+    ///     .map(|entry| entry.key)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(patterns, vec!["(U|k)", ".*"]);
+    /// ```
+    pub fn matching_patterns<'s>(
+        &'s self,
+        key: &'s str,
+    ) -> impl Iterator<Item = &'s Entry<'a>> + 's {
+        self.patterns
+            .iter()
+            .filter(move |(_, pattern)| pattern.is_match(key))
+            .map(|(position, _)| self.effective(*position))
+    }
+
+    /// Retrieve all the occurrences of a key, in order, as they appear in the content.
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &Entry<'a>> {
+        self.index
+            .get(key)
+            .into_iter()
+            .flatten()
+            .map(|&position| &self.entries[position])
+    }
+
+    /// Check if the (last occurrence of the) key was copied from another dictionary by a merge
+    /// (`$dict;`), instead of being set in the dictionary itself. Keys that don't exist are not
+    /// merged.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     p { solver PCG; relTol 0.05; }
+    ///     pFinal { $p; relTol 0; }
+    /// ").unwrap();
+    /// let expanded = root.expand().unwrap();
+    /// let p_final = expanded.get_first_dict("pFinal").unwrap();
+    /// assert!(p_final.is_merged("solver"));
+    /// assert!(!p_final.is_merged("relTol"));
+    /// ```
+    pub fn is_merged(&self, key: &str) -> bool {
+        self.get_entry(key)
+            .is_some_and(|entry| entry.merged_from.is_some())
+    }
+
+    /// Check if the key exists in the dictionary.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Number of entries in the dictionary, including repeated keys.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the dictionary has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over all the entries, in order, including repeated keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &[Foam<'a>])> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key, entry.values.as_slice()))
+    }
+
+    /// All the entries, in order, including repeated keys.
+    pub fn entries(&self) -> &[Entry<'a>] {
+        &self.entries
+    }
+
+    /// Iterate over the keys, in order. Keys that appear more than once are listed only once, in
+    /// the position they first appear.
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(position, entry)| {
+                self.index[entry.key].first() == Some(position)
+            })
+            .map(|(_, entry)| entry.key)
+    }
+
+    /// The entries with code (`code`, `codeInclude`, `codeOptions` and `codeLibs`).
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     momentOfInertia #codeStream
+    ///     {
+    ///         codeInclude #{ #include \"diagTensor.H\" #};
+    ///         code #{ os << $mass; #};
+    ///     };
+    /// ").unwrap();
+    /// let Foam::CodeStream(code_stream) = root.get_first("momentOfInertia").unwrap() else {
+    ///     panic!("not a #codeStream");
+    /// };
+    /// let coded = code_stream.coded();
+    /// assert_eq!(coded.code, Some(" os << $mass; "));
+    /// assert_eq!(coded.code_include, Some(" #include \"diagTensor.H\" "));
+    /// assert_eq!(coded.code_libs, None);
+    /// ```
+    pub fn coded(&self) -> Coded<'a> {
+        let code = |key| match self.get(key) {
+            Some(
+                [Foam::Verbatim(code) | Foam::String(code) | Foam::Value(code)],
+            ) => Some(*code),
+            _ => None,
+        };
+        Coded {
+            code: code("code"),
+            code_include: code("codeInclude"),
+            code_options: code("codeOptions"),
+            code_libs: code("codeLibs"),
+        }
+    }
+
+    /// List the keys that appear more than once, with all their occurrences. Only the last
+    /// occurrence of each key is used; the others are overridden (or, for dictionaries, merged
+    /// with it). Directives (like `#include`) are not entries, so repeating them is fine.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("a 1;\nb 2;\na 3;").unwrap();
+    /// let dict = root.as_dict().unwrap();
+    /// let duplicates = dict.duplicates();
+    /// assert_eq!(duplicates.len(), 1);
+    /// let (key, entries) = &duplicates[0];
+    /// assert_eq!(*key, "a");
+    /// let lines = entries
+    ///     .iter()
+    ///     .map(|entry| entry.location.as_ref().unwrap().line)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(lines, vec![1, 3]);
+    /// ```
+    pub fn duplicates(&self) -> Vec<(&'a str, Vec<&Entry<'a>>)> {
+        self.keys()
+            .filter(|key| !key.starts_with('#') && self.index[key].len() > 1)
+            .map(|key| (key, self.get_all(key).collect()))
+            .collect()
+    }
+}
+
+/// Compile a regular expression used as key, like `"inlet.*"` or in `#remove ("inlet.*")`. Like
+/// OpenFOAM, the expression must match the whole key.
+pub(crate) fn key_pattern(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&format!("^(?:{})$", pattern))
+}
+
+/// Dictionaries are compared by their entries, in order.
+impl PartialEq for Dictionary<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+/// Entries are compared by their keys and values; where they are in the content doesn't matter.
+impl PartialEq for Entry<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+            && self.quoted == other.quoted
+            && self.values == other.values
+    }
+}
+
+impl<'a> Entry<'a> {
+    /// Create a new entry that came from the content. Values are added later.
+    pub(crate) fn new(key: &'a str, quoted: bool, location: Location) -> Self {
+        Self {
+            key,
+            quoted,
+            values: Vec::new(),
+            location: Some(location),
+            merged_from: None,
+        }
+    }
+}
+
+impl<'a> FromIterator<(&'a str, Vec<Foam<'a>>)> for Dictionary<'a> {
+    fn from_iter<T: IntoIterator<Item = (&'a str, Vec<Foam<'a>>)>>(
+        iter: T,
+    ) -> Self {
+        let mut dict = Dictionary::new();
+        for (key, values) in iter {
+            dict.insert(key, values);
+        }
+        dict
+    }
+}
+
+impl<'a, const N: usize> From<[(&'a str, Vec<Foam<'a>>); N]>
+    for Dictionary<'a>
+{
+    fn from(entries: [(&'a str, Vec<Foam<'a>>); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl std::fmt::Debug for Dictionary<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keeps_order() {
+        let root = Foam::parse("c 1; a 2; b { z 1; y 2; } d 4;").unwrap();
+        let dict = root.as_dict().unwrap();
+        assert_eq!(dict.keys().collect::<Vec<_>>(), vec!["c", "a", "b", "d"]);
+        let inner = root.get_first_dict("b").unwrap();
+        assert_eq!(inner.keys().collect::<Vec<_>>(), vec!["z", "y"]);
+    }
+
+    #[test]
+    fn order_matters_for_equality() {
+        let first = Dictionary::from([
+            ("a", vec![Foam::Value("1")]),
+            ("b", vec![Foam::Value("2")]),
+        ]);
+        let second = Dictionary::from([
+            ("b", vec![Foam::Value("2")]),
+            ("a", vec![Foam::Value("1")]),
+        ]);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn last_wins_and_dictionaries_merge() {
+        let root = Foam::parse("a 1; b { x 1; } a 2; b { y 2; }").unwrap();
+        assert_eq!(root.get_first_value("a"), Ok("2"));
+        let b = root.get_first_dict("b").unwrap();
+        assert_eq!(b.keys().collect::<Vec<_>>(), vec!["x", "y"]);
+
+        let dict = root.as_dict().unwrap();
+        let all_a = dict
+            .get_all("a")
+            .map(|entry| entry.values.as_slice())
+            .collect::<Vec<_>>();
+        assert_eq!(all_a, vec![[Foam::Value("1")], [Foam::Value("2")]]);
+        assert_eq!(dict.len(), 4);
+    }
+
+    #[test]
+    fn nested_dictionaries_merge() {
+        let root = Foam::parse(
+            "solvers
+            {
+                p { solver GAMG; tolerance 1e-6; relTol 0.05; }
+                U { solver smoothSolver; }
+            }
+            solvers { p { tolerance 1e-8; } }
+            c { x 1; } c 2;
+            d 1; d { x 1; }",
+        )
+        .unwrap();
+        let solvers = root.get_first("solvers").unwrap();
+        assert_eq!(
+            solvers.as_dict().unwrap().keys().collect::<Vec<_>>(),
+            vec!["p", "U"]
+        );
+        let p = solvers.get_first("p").unwrap();
+        assert_eq!(p.get_first_value("solver"), Ok("GAMG"));
+        assert_eq!(p.get_first_value("tolerance"), Ok("1e-8"));
+        assert_eq!(p.get_first_value("relTol"), Ok("0.05"));
+        let u = solvers.get_first("U").unwrap();
+        assert_eq!(u.get_first_value("solver"), Ok("smoothSolver"));
+
+        // Only dictionaries merge; anything else replaces them, and is replaced by them.
+        assert_eq!(root.get_first_value("c"), Ok("2"));
+        let d = root.get_first_dict("d").unwrap();
+        assert_eq!(d.keys().collect::<Vec<_>>(), vec!["x"]);
+
+        // The tree keeps the occurrences as they are in the content.
+        let dict = root.as_dict().unwrap();
+        let last = dict.get_all("solvers").last().unwrap();
+        let last = last.values[0].as_dict().unwrap();
+        assert_eq!(last.keys().collect::<Vec<_>>(), vec!["p"]);
+    }
+
+    #[test]
+    fn duplicates_locations() {
+        let root =
+            Foam::parse("p\n{\n  tol 1;\n  tol 2;\n}\nq 1;\np { }").unwrap();
+        let dict = root.as_dict().unwrap();
+        let duplicates = dict
+            .duplicates()
+            .into_iter()
+            .map(|(key, entries)| {
+                let positions = entries
+                    .iter()
+                    .map(|entry| {
+                        let location = entry.location.as_ref().unwrap();
+                        (location.line, location.column)
+                    })
+                    .collect::<Vec<_>>();
+                (key, positions)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(duplicates, vec![("p", vec![(1, 1), (7, 1)])]);
+
+        let inner = dict.get_all("p").next().unwrap();
+        let inner = inner.values[0].as_dict().unwrap();
+        let inner_duplicates = inner.duplicates();
+        assert_eq!(inner_duplicates.len(), 1);
+        let locations = inner_duplicates[0]
+            .1
+            .iter()
+            .map(|entry| entry.location.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            vec![
+                Location {
+                    span: 6..9,
+                    line: 3,
+                    column: 3
+                },
+                Location {
+                    span: 15..18,
+                    line: 4,
+                    column: 3
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn no_duplicates() {
+        let root = Foam::parse("a 1; b 2;").unwrap();
+        assert!(root.as_dict().unwrap().duplicates().is_empty());
+    }
+
+    #[test]
+    fn lookup_precedence() {
+        let root =
+            Foam::parse("inlet 1; \"inlet.*\" 2; \"in.*\" 3; \"wall\" 5;")
+                .unwrap();
+        let dict = root.as_dict().unwrap();
+        // Literal keys win, even if a pattern comes after them.
+        assert_eq!(dict.lookup("inlet"), Some([Foam::Value("1")].as_slice()));
+        // Otherwise, the last pattern that matches.
+        assert_eq!(dict.lookup("inlet2"), Some([Foam::Value("3")].as_slice()));
+        assert_eq!(dict.lookup("wall"), Some([Foam::Value("5")].as_slice()));
+        // Patterns must match the whole key.
+        assert_eq!(dict.lookup("outlet"), None);
+
+        let patterns = dict
+            .matching_patterns("inlet2")
+            .map(|entry| entry.key)
+            .collect::<Vec<_>>();
+        assert_eq!(patterns, vec!["inlet.*", "in.*"]);
+    }
+
+    #[test]
+    fn unquoted_keys_are_not_patterns() {
+        let root = Foam::parse("a.* 1;").unwrap();
+        let dict = root.as_dict().unwrap();
+        assert_eq!(dict.lookup("abc"), None);
+        assert_eq!(dict.lookup("a.*"), Some([Foam::Value("1")].as_slice()));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        // Valid for OpenFOAM (backreferences), but not for the regex crate.
+        let content = "a 1; \"(b)\\1\" 2; c 3;";
+        let root = Foam::parse(content).unwrap();
+        let dict = root.as_dict().unwrap();
+        assert_eq!(dict.len(), 3);
+        assert_eq!(dict.lookup("(b)\\1"), Some([Foam::Value("2")].as_slice()));
+        assert_eq!(dict.lookup("bb"), None);
+
+        let (recovered, errors) = Foam::parse_recovering(content);
+        assert!(matches!(
+            errors[..],
+            [FoamError::InvalidPattern {
+                pattern: "(b)\\1",
+                ..
+            }]
+        ));
+        assert_eq!(recovered, root);
+    }
+
+    #[test]
+    fn patterns_after_retain() {
+        let root = Foam::parse("\"a.*\" 1; b 2; \"b.*\" 3;").unwrap();
+        let mut dict = root.as_dict().unwrap().clone();
+        dict.retain(|entry| entry.key != "a.*");
+        assert_eq!(dict.lookup("abc"), None);
+        assert_eq!(dict.lookup("bcd"), Some([Foam::Value("3")].as_slice()));
+        assert_eq!(dict.lookup("b"), Some([Foam::Value("2")].as_slice()));
+    }
+}
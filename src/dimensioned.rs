@@ -0,0 +1,378 @@
+//! Dimensioned types, like `nu [0 2 -1 0 0 0 0] 1e-05;`.
+
+use std::fmt::Display;
+
+use crate::calc::format;
+use crate::DimensionedScalar;
+use crate::DimensionedVector;
+use crate::Dimensions;
+use crate::Entry;
+use crate::Foam;
+use crate::FoamError;
+use crate::Vector;
+
+/// The units that can be used by name: the SI base units, in the same order as the exponents in
+/// [`Dimensions`], and the derived units OpenFOAM files commonly use.
+const UNITS: [(&str, [f64; 7]); 12] = [
+    ("kg", [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+    ("m", [0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]),
+    ("s", [0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]),
+    ("K", [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]),
+    ("mol", [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+    ("A", [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]),
+    ("cd", [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]),
+    ("N", [1.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0]),
+    ("Pa", [1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0]),
+    ("J", [1.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0]),
+    ("W", [1.0, 2.0, -3.0, 0.0, 0.0, 0.0, 0.0]),
+    ("Hz", [0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.0]),
+];
+
+impl Dimensions {
+    /// Read the dimensions from the entries of a dimension set: 7 exponents, the short form with
+    /// 5 exponents (no current or luminous intensity) or units with their exponents, like
+    /// `[m^2 s^-1]`, `[m^2/s]` or `[N]`. Units are the SI base units (`kg`, `m`, `s`, `K`, `mol`,
+    /// `A` and `cd`) and the derived `N`, `Pa`, `J`, `W` and `Hz`; they can be multiplied with
+    /// spaces or `*` and divided with `/`. Returns why the dimensions are invalid otherwise.
+    ///
+    /// ```
+    /// # use foamparser::Dimensions;
+    /// let viscosity = Dimensions([0.0, 2.0, -1.0, 0.0, 0.0, 0.0, 0.0]);
+    /// assert_eq!(Dimensions::parse(&["0", "2", "-1", "0", "0", "0", "0"]), Ok(viscosity));
+    /// assert_eq!(Dimensions::parse(&["0", "2", "-1", "0", "0"]), Ok(viscosity));
+    /// assert_eq!(Dimensions::parse(&["m^2", "s^-1"]), Ok(viscosity));
+    /// assert_eq!(Dimensions::parse(&["m^2/s"]), Ok(viscosity));
+    /// let pressure = Dimensions([1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0]);
+    /// assert_eq!(Dimensions::parse(&["Pa"]), Ok(pressure));
+    /// assert_eq!(Dimensions::parse(&["N/m^2"]), Ok(pressure));
+    /// assert!(Dimensions::parse(&["0", "2", "-1"]).is_err());
+    /// ```
+    pub fn parse(entries: &[&str]) -> Result<Self, String> {
+        let exponents: Result<Vec<f64>, _> =
+            entries.iter().map(|entry| entry.parse::<f64>()).collect();
+        match exponents {
+            Ok(exponents) if exponents.len() == 7 || exponents.len() == 5 => {
+                let mut result = [0.0; 7];
+                result[..exponents.len()].copy_from_slice(&exponents);
+                Ok(Self(result))
+            }
+            Ok(exponents) => Err(format!(
+                "expected 5 or 7 exponents, found {}",
+                exponents.len()
+            )),
+            Err(_) => Self::from_units(entries),
+        }
+    }
+
+    /// Read the dimensions from units with their exponents, like `[kg m^-3]` or `[kg/m^3]`.
+    fn from_units(entries: &[&str]) -> Result<Self, String> {
+        let mut result = [0.0; 7];
+        for entry in entries {
+            // Each unit is multiplied or divided by the ones before it in the same entry.
+            let mut sign = 1.0;
+            let mut rest = *entry;
+            loop {
+                let end = rest.find(['*', '/']).unwrap_or(rest.len());
+                let (unit, exponent) = match rest[..end].split_once('^') {
+                    Some((unit, exponent)) => {
+                        let exponent =
+                            exponent.parse::<f64>().map_err(|_| {
+                                format!("invalid exponent in {:?}", entry)
+                            })?;
+                        (unit, exponent)
+                    }
+                    None => (&rest[..end], 1.0),
+                };
+                if unit.is_empty() {
+                    return Err(format!("missing unit in {:?}", entry));
+                }
+                let (_, base) = UNITS
+                    .iter()
+                    .find(|(known, _)| *known == unit)
+                    .ok_or_else(|| format!("unknown unit {:?}", unit))?;
+                for (total, base) in result.iter_mut().zip(base) {
+                    *total += sign * exponent * base;
+                }
+
+                match rest[end..].chars().next() {
+                    Some(operator) => {
+                        sign = if operator == '/' { -1.0 } else { 1.0 };
+                        rest = &rest[end + 1..];
+                    }
+                    None => break,
+                }
+            }
+        }
+        Ok(Self(result))
+    }
+}
+
+impl Display for Dimensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let exponents: Vec<String> =
+            self.0.iter().map(|x| format(*x)).collect();
+        write!(f, "[{}]", exponents.join(" "))
+    }
+}
+
+/// Split the values of a dimensioned entry into its name, its dimensions and its value. The name
+/// is optional and defaults to the key.
+fn parts<'e, 'a>(
+    entry: &'e Entry<'a>,
+) -> Result<(&'a str, Dimensions, &'e Foam<'a>), FoamError<'a>> {
+    let (name, rest) = match entry.values.as_slice() {
+        [Foam::Value(name), rest @ ..] => (*name, rest),
+        rest => (entry.key, rest),
+    };
+    let (dimensions, rest) = match rest {
+        [Foam::Dimension(dimensions), rest @ ..] => (dimensions, rest),
+        _ => {
+            return Err(FoamError::MissingDimensions {
+                key: entry.key,
+                location: entry.location.clone(),
+            })
+        }
+    };
+    let dimensions = Dimensions::parse(dimensions).map_err(|reason| {
+        FoamError::InvalidDimensions {
+            key: entry.key,
+            reason,
+            location: entry.location.clone(),
+        }
+    })?;
+    let invalid = |reason| FoamError::InvalidDimensionedValue {
+        key: entry.key,
+        reason,
+        location: entry.location.clone(),
+    };
+    match rest {
+        [value] => Ok((name, dimensions, value)),
+        [] => Err(invalid("missing value after the dimensions")),
+        _ => Err(invalid("more than one value after the dimensions")),
+    }
+}
+
+impl<'e, 'a> TryFrom<&'e Entry<'a>> for DimensionedScalar<'a> {
+    type Error = FoamError<'e>;
+
+    fn try_from(entry: &'e Entry<'a>) -> Result<Self, Self::Error> {
+        let (name, dimensions, value) = parts(entry)?;
+        let text = match value {
+            Foam::Value(text) => *text,
+            Foam::Calculated(text) => text.as_str(),
+            _ => return Err(FoamError::NotAValue),
+        };
+        let value = text.parse().map_err(|_| FoamError::InvalidNumber {
+            key: entry.key.to_string(),
+            text,
+        })?;
+        Ok(Self {
+            name,
+            dimensions,
+            value,
+        })
+    }
+}
+
+impl<'e, 'a> TryFrom<&'e Entry<'a>> for DimensionedVector<'a> {
+    type Error = FoamError<'e>;
+
+    fn try_from(entry: &'e Entry<'a>) -> Result<Self, Self::Error> {
+        let (name, dimensions, value) = parts(entry)?;
+        Ok(Self {
+            name,
+            dimensions,
+            value: Vector::try_from(value)?,
+        })
+    }
+}
+
+/// Written as the name, the dimensions and the value. Put after the key, that's the full form of
+/// the entry.
+///
+/// ```
+/// # use foamparser::Foam;
+/// let root = Foam::parse("nu [m^2 s^-1] 1e-6;").unwrap();
+/// let nu = root.get_dimensioned_scalar("nu").unwrap();
+/// assert_eq!(nu.to_string(), "nu [0 2 -1 0 0 0 0] 1e-6");
+/// assert_eq!(format!("nu {};", nu), "nu nu [0 2 -1 0 0 0 0] 1e-6;");
+/// ```
+impl Display for DimensionedScalar<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.name,
+            self.dimensions,
+            format(self.value)
+        )
+    }
+}
+
+/// Written as the name, the dimensions and the value, like `g [0 1 -2 0 0 0 0] (0 -9.81 0)`. Put
+/// after the key, that's the full form of the entry.
+impl Display for DimensionedVector<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.name, self.dimensions, self.value)
+    }
+}
+
+impl<'a> Foam<'a> {
+    /// Retrieve a dimensioned number from a dictionary. The name is optional, so both
+    /// `nu nu [0 2 -1 0 0 0 0] 1;` and `nu [0 2 -1 0 0 0 0] 1;` work.
+    ///
+    /// ```
+    /// # use foamparser::Dimensions;
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("nu [0 2 -1 0 0 0 0] 1e-05;").unwrap();
+    /// let nu = root.get_dimensioned_scalar("nu").unwrap();
+    /// assert_eq!(nu.name, "nu");
+    /// assert_eq!(nu.dimensions, Dimensions([0.0, 2.0, -1.0, 0.0, 0.0, 0.0, 0.0]));
+    /// assert_eq!(nu.value, 1e-5);
+    /// ```
+    ///
+    /// Entries without dimensions fail with [`FoamError::MissingDimensions`], and dimensions
+    /// that can't be read fail with [`FoamError::InvalidDimensions`].
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("nu 1e-05;").unwrap();
+    /// let nu = root.get_dimensioned_scalar("nu");
+    /// assert!(matches!(nu, Err(FoamError::MissingDimensions { key: "nu", .. })));
+    /// ```
+    pub fn get_dimensioned_scalar(
+        &self,
+        key: &str,
+    ) -> Result<DimensionedScalar<'a>, FoamError<'_>> {
+        let entry =
+            self.as_dict()?.get_entry(key).ok_or(FoamError::NoSuchKey)?;
+        DimensionedScalar::try_from(entry)
+    }
+
+    /// Retrieve a dimensioned vector from a dictionary, like [`Foam::get_dimensioned_scalar`].
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::Vector;
+    /// let root = Foam::parse("g g [0 1 -2 0 0 0 0] (0 -9.81 0);").unwrap();
+    /// let g = root.get_dimensioned_vector("g").unwrap();
+    /// assert_eq!(g.value, Vector::new(0.0, -9.81, 0.0));
+    /// assert_eq!(g.to_string(), "g [0 1 -2 0 0 0 0] (0 -9.81 0)");
+    /// ```
+    pub fn get_dimensioned_vector(
+        &self,
+        key: &str,
+    ) -> Result<DimensionedVector<'a>, FoamError<'_>> {
+        let entry =
+            self.as_dict()?.get_entry(key).ok_or(FoamError::NoSuchKey)?;
+        DimensionedVector::try_from(entry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::DimensionedScalar;
+    use crate::Dimensions;
+    use crate::Foam;
+    use crate::FoamError;
+
+    #[test]
+    fn named_and_unnamed() {
+        let root = Foam::parse(
+            "nu nu [0 2 -1 0 0 0 0] 1;\nmu dynamic [1 -1 -1 0 0] 2;",
+        )
+        .unwrap();
+        assert_eq!(
+            root.get_dimensioned_scalar("nu"),
+            Ok(DimensionedScalar {
+                name: "nu",
+                dimensions: Dimensions([0.0, 2.0, -1.0, 0.0, 0.0, 0.0, 0.0]),
+                value: 1.0,
+            })
+        );
+        let mu = root.get_dimensioned_scalar("mu").unwrap();
+        assert_eq!(mu.name, "dynamic");
+        assert_eq!(mu.dimensions.0, [1.0, -1.0, -1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(mu.to_string(), "dynamic [1 -1 -1 0 0 0 0] 2");
+    }
+
+    #[test]
+    fn units() {
+        let root = Foam::parse("rho [kg m^-3] 1000;").unwrap();
+        let rho = root.get_dimensioned_scalar("rho").unwrap();
+        assert_eq!(rho.dimensions.0, [1.0, -3.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn derived_units() {
+        let root = Foam::parse(
+            "U [m/s] 1; g [m/s^2] 9.81; p [Pa] 1e5; F [kg*m/s^2] 1; e [J/kg] 1; mu [Pa s] 1;",
+        )
+        .unwrap();
+        let dimensions =
+            |key| root.get_dimensioned_scalar(key).unwrap().dimensions.0;
+        assert_eq!(dimensions("U"), [0.0, 1.0, -1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(dimensions("g"), [0.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(dimensions("p"), [1.0, -1.0, -2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(dimensions("F"), [1.0, 1.0, -2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(dimensions("e"), [0.0, 2.0, -2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(dimensions("mu"), [1.0, -1.0, -1.0, 0.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(
+            Dimensions::parse(&["m/"]),
+            Err("missing unit in \"m/\"".to_string())
+        );
+        assert_eq!(
+            Dimensions::parse(&["m//s"]),
+            Err("missing unit in \"m//s\"".to_string())
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let root = Foam::parse(
+            "a [0 2 -1] 1;\nb [m^2 furlong] 1;\nc [0 2 -1 0 0] x;\nd [0 2 -1 0 0];",
+        )
+        .unwrap();
+        assert!(matches!(
+            root.get_dimensioned_scalar("a"),
+            Err(FoamError::InvalidDimensions { key: "a", reason, location: Some(_) })
+                if reason == "expected 5 or 7 exponents, found 3"
+        ));
+        assert!(matches!(
+            root.get_dimensioned_scalar("b"),
+            Err(FoamError::InvalidDimensions { key: "b", reason, .. })
+                if reason == "unknown unit \"furlong\""
+        ));
+        assert_eq!(
+            root.get_dimensioned_scalar("c"),
+            Err(FoamError::InvalidNumber {
+                key: "c".into(),
+                text: "x"
+            })
+        );
+        assert!(matches!(
+            root.get_dimensioned_scalar("d"),
+            Err(FoamError::InvalidDimensionedValue {
+                key: "d",
+                reason: "missing value after the dimensions",
+                location: Some(_),
+            })
+        ));
+        assert!(matches!(
+            root.get_dimensioned_vector("c"),
+            Err(FoamError::NotAList)
+        ));
+
+        let root = Foam::parse("e [0 2 -1 0 0] 1 2;").unwrap();
+        assert!(matches!(
+            root.get_dimensioned_scalar("e"),
+            Err(FoamError::InvalidDimensionedValue {
+                key: "e",
+                reason: "more than one value after the dimensions",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,158 @@
+//! Environment variables used when expanding the tree.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::Environment;
+
+impl Environment {
+    /// Create an environment without any variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an environment with the variables of the current process.
+    pub fn from_process() -> Self {
+        Self {
+            process: true,
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Set a variable, replacing its value in the process environment, if any.
+    ///
+    /// ```
+    /// # use foamparser::Environment;
+    /// let environment = Environment::new()
+    ///     .with("FOAM_CASE", "/cluster/run/pitzDaily")
+    ///     .with("FOAM_APPLICATION", "simpleFoam");
+    /// assert_eq!(environment.get("FOAM_APPLICATION"), Some("simpleFoam".into()));
+    /// assert_eq!(environment.get("HOME"), None);
+    /// ```
+    pub fn with(mut self, name: &str, value: &str) -> Self {
+        self.variables.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// The value of a variable, if it's defined. Like OpenFOAM, `FOAM_CASENAME` defaults to the
+    /// last directory of `FOAM_CASE`.
+    ///
+    /// ```
+    /// # use foamparser::Environment;
+    /// let environment = Environment::new().with("FOAM_CASE", "/run/pitzDaily");
+    /// assert_eq!(environment.get("FOAM_CASENAME"), Some("pitzDaily".into()));
+    /// ```
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.variables
+            .get(name)
+            .cloned()
+            .or_else(|| {
+                self.process.then(|| std::env::var(name).ok()).flatten()
+            })
+            .or_else(|| match name {
+                "FOAM_CASENAME" => self.get("FOAM_CASE").and_then(|case| {
+                    Path::new(&case)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                }),
+                _ => None,
+            })
+    }
+
+    /// Replace the variables (`$NAME` or `${NAME}`) in a text, like the path of an included
+    /// file. Variables that are not defined are kept as they are.
+    ///
+    /// ```
+    /// # use foamparser::Environment;
+    /// let environment = Environment::new().with("FOAM_RUN", "/run");
+    /// assert_eq!(
+    ///     environment.expand("$FOAM_RUN/pitzDaily/${FOAM_CASENAME}/U"),
+    ///     "/run/pitzDaily/${FOAM_CASENAME}/U"
+    /// );
+    /// ```
+    pub fn expand(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let (name, size) = match rest[1..].strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], end + 3),
+                    None => ("", 1),
+                },
+                None => {
+                    let end = rest[1..]
+                        .find(|char: char| {
+                            !char.is_ascii_alphanumeric() && char != '_'
+                        })
+                        .unwrap_or(rest.len() - 1);
+                    (&rest[1..=end], end + 1)
+                }
+            };
+            match self.get(name).filter(|_| !name.is_empty()) {
+                Some(value) => result.push_str(&value),
+                None => result.push_str(&rest[..size]),
+            }
+            rest = &rest[size..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Environment
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    /// Create an environment with only the variables in the iterator (e.g., a `HashMap`).
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self {
+            process: false,
+            variables: iter
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::Environment;
+
+    #[test]
+    fn explicit_values() {
+        let environment = Environment::from_iter(HashMap::from([
+            ("FOAM_CASE", "/run/cavity"),
+            ("FOAM_CASENAME", "other"),
+        ]));
+        assert_eq!(environment.get("FOAM_CASE"), Some("/run/cavity".into()));
+        assert_eq!(environment.get("FOAM_CASENAME"), Some("other".into()));
+        assert_eq!(environment.get("PATH"), None);
+    }
+
+    #[test]
+    fn process() {
+        let Ok(path) = std::env::var("PATH") else {
+            return;
+        };
+        let environment = Environment::from_process().with("HOME", "/nowhere");
+        assert_eq!(environment.get("PATH"), Some(path));
+        assert_eq!(environment.get("HOME"), Some("/nowhere".into()));
+    }
+
+    #[test]
+    fn expand() {
+        let environment = Environment::new().with("A", "1").with("B_2", "two");
+        assert_eq!(environment.expand("$A/$B_2.txt"), "1/two.txt");
+        assert_eq!(environment.expand("${A}x ${B_2}"), "1x two");
+        assert_eq!(environment.expand("$C/${C} $ ${A"), "$C/${C} $ ${A");
+        assert_eq!(environment.expand("no variables"), "no variables");
+        assert_eq!(environment.expand("ends with $A"), "ends with 1");
+    }
+}
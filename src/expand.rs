@@ -0,0 +1,1536 @@
+//! Expands macros (`$var`) in the tree.
+
+use std::borrow::Cow;
+
+use crate::access::switch;
+use crate::calc;
+use crate::calc::Number;
+use crate::dictionary::key_pattern;
+use crate::Conditional;
+use crate::Context;
+use crate::Dictionary;
+use crate::Entry;
+use crate::Foam;
+use crate::FoamError;
+use crate::IncludeKind;
+
+impl<'a> Foam<'a> {
+    /// Expand all the macros in the tree, returning a new tree without them.
+    ///
+    /// Macros are values starting with `$`, which are replaced by the values of the entry they
+    /// point to. Like OpenFOAM, the following forms are supported:
+    ///
+    /// - `$a`: the entry `a` in the current dictionary or, if it doesn't exist there, in any of the
+    ///   dictionaries above it;
+    /// - `$subdict/a`: the entry `a` inside `subdict`;
+    /// - `$../a`: the entry `a` in the dictionary above the current one (`$../../a` for two levels
+    ///   above, and so on);
+    /// - `$!a`: the entry `a` in the root dictionary;
+    /// - `${a}`: same as `$a`, but the name can be another macro, e.g. `${${b}}`.
+    ///
+    /// Conditional blocks (`#if`/`#ifeq` ... `#else` ... `#endif`) are replaced by the entries of
+    /// the branch selected by their condition. `#if` expects a switch (like `true` or `off`), a
+    /// number or an expression (`#calc "..."`); `#ifeq` compares two words or numbers.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     solver simpleFoam;
+    ///     ddtSchemes
+    ///     {
+    ///     #ifeq $solver simpleFoam
+    ///         default steadyState;
+    ///     #else
+    ///         default Euler;
+    ///     #endif
+    ///     }
+    /// ").unwrap();
+    /// let expanded = root.expand().unwrap();
+    /// let ddt_schemes = expanded.get_first("ddtSchemes").unwrap();
+    /// assert_eq!(ddt_schemes.get_first_value("default"), Ok("steadyState"));
+    /// ```
+    ///
+    /// The expressions of `#calc` and `#eval` directives are evaluated and replaced by their
+    /// results ([`Foam::Calculated`] or, for vectors, a list of them). Expressions can use
+    /// arithmetic and comparison operators, functions like `sin`, `sqrt`, `pow`, `degToRad` or
+    /// `mag`, vectors (like `vector(1, 0, 0)`) and macros; macros with more than a name must use
+    /// braces, like `${../radius}`. All numbers are floating point, so, unlike the C++ code
+    /// OpenFOAM compiles, `7/2` is `3.5` and not `3`.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     halfAngle 45.0;
+    ///     radius 0.5;
+    ///     radHalfAngle #calc \"degToRad($halfAngle)\";
+    ///     y #calc \"$radius*sin($radHalfAngle)\";
+    ///     v #eval \"vector($y, 0, 1) * 2\";
+    /// ").unwrap();
+    /// let expanded = root.expand().unwrap();
+    /// assert_eq!(expanded.get_first_value("y"), Ok("0.35355339059327373"));
+    /// let v = expanded.get_first_list("v").unwrap();
+    /// assert_eq!(v[0], Foam::Calculated("0.7071067811865475".into()));
+    /// ```
+    ///
+    /// `#remove` directives remove the entries that appear before them, by key (`#remove a`),
+    /// regular expression (`#remove "inlet.*"`) or a list of both (`#remove (a "inlet.*")`).
+    ///
+    /// An entry with just a macro pointing to a dictionary (e.g., `$p;`) is a merge: all the
+    /// entries of that dictionary are copied into the current one, at that position. Entries that
+    /// come after it replace the copied ones (see [`crate::Dictionary::is_merged`]).
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let root = Foam::parse("
+    ///     a 10;
+    ///     b a;
+    ///     c ${${b}};
+    ///     subdictA { a 20; }
+    ///     subdictB {
+    ///         b $../subdictA/a;
+    ///         subsubdict { b $!a; }
+    ///     }
+    /// ").unwrap();
+    /// let expanded = root.expand().unwrap();
+    /// assert_eq!(expanded.get_first_value("c"), Ok("10"));
+    /// let subdict_b = expanded.get_first("subdictB").unwrap();
+    /// assert_eq!(subdict_b.get_first_value("b"), Ok("20"));
+    /// let subsubdict = subdict_b.get_first("subsubdict").unwrap();
+    /// assert_eq!(subsubdict.get_first_value("b"), Ok("10"));
+    /// ```
+    ///
+    /// Entries are expanded in order, so macros can only use entries that appear before them; if
+    /// the entry doesn't exist (yet), the expansion fails with [`FoamError::UnresolvedReference`].
+    /// Macros pointing to the entry they are part of fail with [`FoamError::CyclicReference`].
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::FoamError;
+    /// let root = Foam::parse("b $a; a 1;").unwrap();
+    /// assert!(matches!(
+    ///     root.expand(),
+    ///     Err(FoamError::UnresolvedReference { reference: "$a", .. })
+    /// ));
+    ///
+    /// let root = Foam::parse("p { solver $p; }").unwrap();
+    /// assert!(matches!(
+    ///     root.expand(),
+    ///     Err(FoamError::CyclicReference { reference: "$p", .. })
+    /// ));
+    /// ```
+    pub fn expand(&self) -> Result<Foam<'a>, FoamError<'a>> {
+        match self {
+            Foam::Dictionary(root) => {
+                Expander::default().dict(root).map(Foam::Dictionary)
+            }
+            _ => Err(FoamError::NotADictionary),
+        }
+    }
+
+    /// Expand all the macros in the tree, like [`Foam::expand`], also processing the directives
+    /// that need something besides the tree itself.
+    ///
+    /// With a [`crate::Resolver`] in the context, `#include`, `#includeIfPresent`, `#includeEtc`
+    /// and `#includeFunc` are replaced by the entries of the files they point to, parsed with the
+    /// same rules, and macros can point to entries in other files (`$file!a`). Included files
+    /// can include other files, but files including each other fail with
+    /// [`FoamError::IncludeCycle`]; errors in included files are wrapped in
+    /// [`FoamError::InInclude`], naming the files they are in.
+    ///
+    /// ```
+    /// # use std::collections::HashMap;
+    /// # use foamparser::Context;
+    /// # use foamparser::Foam;
+    /// let files = HashMap::from([
+    ///     ("initialConditions".to_string(), "pressure 1e5;".to_string()),
+    /// ]);
+    /// let context = Context::new().with_resolver(files);
+    /// let root = Foam::parse("
+    ///     #include \"initialConditions\"
+    ///     internalField uniform $pressure;
+    ///     #includeIfPresent \"optional\"
+    /// ").unwrap();
+    /// let expanded = root.expand_with(&context).unwrap();
+    /// assert_eq!(expanded.get_first_value("pressure"), Ok("1e5"));
+    /// assert_eq!(expanded.get("internalField").unwrap().len(), 2);
+    /// ```
+    ///
+    /// Without a resolver, the include directives are kept as they are.
+    ///
+    /// Macros that don't point to any entry are looked up in the [`crate::Environment`] of the
+    /// context, as are the variables in the paths of included files (e.g.,
+    /// `#include "$FOAM_CASE/constant/common"`); variables that are not defined are kept in paths
+    /// as they are, and fail with [`FoamError::UnresolvedReference`] elsewhere.
+    ///
+    /// ```
+    /// # use foamparser::Context;
+    /// # use foamparser::Environment;
+    /// # use foamparser::Foam;
+    /// let environment = Environment::new().with("FOAM_APPLICATION", "simpleFoam");
+    /// let context = Context::new().with_environment(environment);
+    /// let root = Foam::parse("
+    ///     application $FOAM_APPLICATION;
+    ///     #ifeq ${FOAM_APPLICATION} simpleFoam
+    ///     steady yes;
+    ///     #endif
+    /// ").unwrap();
+    /// let expanded = root.expand_with(&context).unwrap();
+    /// assert_eq!(expanded.get_first_value("application"), Ok("simpleFoam"));
+    /// assert_eq!(expanded.get_first_value("steady"), Ok("yes"));
+    /// ```
+    pub fn expand_with<'c>(
+        &self,
+        context: &'c Context,
+    ) -> Result<Foam<'c>, FoamError<'c>>
+    where
+        'a: 'c,
+    {
+        let root: &Foam<'c> = self;
+        match root {
+            Foam::Dictionary(root) => {
+                Expander::new(context).dict(root).map(Foam::Dictionary)
+            }
+            _ => Err(FoamError::NotADictionary),
+        }
+    }
+}
+
+/// State of the expansion.
+#[derive(Default)]
+struct Expander<'a> {
+    /// Dictionaries being expanded, from the root to the current one. Only the entries that were
+    /// already expanded are in them.
+    scopes: Vec<Dictionary<'a>>,
+
+    /// Keys of the entries being expanded.
+    expanding: Vec<&'a str>,
+
+    /// Where included files come from.
+    context: Option<&'a Context>,
+
+    /// Names of the files being expanded, from the root to the current one.
+    files: Vec<String>,
+}
+
+impl<'a> Expander<'a> {
+    /// Start an expansion using the context.
+    fn new(context: &'a Context) -> Self {
+        Self {
+            context: Some(context),
+            files: context.name().map(String::from).into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Expand all the entries of a dictionary.
+    fn dict(
+        &mut self,
+        dict: &Dictionary<'a>,
+    ) -> Result<Dictionary<'a>, FoamError<'a>> {
+        self.scopes.push(Dictionary::new());
+        self.entries(dict)?;
+        Ok(self.scopes.pop().expect("current scope"))
+    }
+
+    /// Expand the entries of a dictionary into the current one.
+    fn entries(&mut self, dict: &Dictionary<'a>) -> Result<(), FoamError<'a>> {
+        for entry in dict.entries() {
+            if let Some(kind) = IncludeKind::from_directive(entry.key) {
+                if self.include(kind, entry)? {
+                    continue;
+                }
+            }
+
+            if entry.key == "#remove" {
+                self.remove(entry)?;
+                continue;
+            }
+
+            if let [Foam::Conditional(conditional)] = entry.values.as_slice() {
+                let branch = if self.condition(conditional, entry)? {
+                    Some(&conditional.then)
+                } else {
+                    conditional.otherwise.as_ref()
+                };
+                if let Some(branch) = branch {
+                    self.entries(branch)?;
+                }
+                continue;
+            }
+
+            if entry.key.starts_with('$') && entry.values.is_empty() {
+                self.merge(entry)?;
+                continue;
+            }
+
+            self.expanding.push(entry.key);
+            let values = self.values(&entry.values, entry)?;
+            self.expanding.pop();
+
+            let scope = self.scopes.last_mut().expect("current scope");
+            scope.push(Entry {
+                key: entry.key,
+                quoted: entry.quoted,
+                values,
+                location: entry.location.clone(),
+                merged_from: entry.merged_from,
+            });
+        }
+        Ok(())
+    }
+
+    /// Remove the entries a `#remove` directive points to from the current dictionary. Keys can be
+    /// words or quoted regular expressions, alone or in a list.
+    fn remove(&mut self, entry: &Entry<'a>) -> Result<(), FoamError<'a>> {
+        let arguments = match entry.values.as_slice() {
+            [Foam::List(items)] => items.as_slice(),
+            values => values,
+        };
+
+        let mut keys = Vec::new();
+        let mut patterns = Vec::new();
+        for argument in arguments {
+            match argument {
+                Foam::Value(key) => keys.push(*key),
+                Foam::String(pattern) => {
+                    let compiled = key_pattern(pattern).map_err(|error| {
+                        FoamError::InvalidPattern {
+                            pattern,
+                            reason: error.to_string(),
+                            location: entry.location.clone(),
+                        }
+                    })?;
+                    patterns.push(compiled);
+                }
+                _ => {
+                    return Err(FoamError::InvalidDirective {
+                        directive: entry.key,
+                        location: entry.location.clone(),
+                    })
+                }
+            }
+        }
+
+        let scope = self.scopes.last_mut().expect("current scope");
+        scope.retain(|existing| {
+            !keys.contains(&existing.key)
+                && !patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(existing.key))
+        });
+        Ok(())
+    }
+
+    /// Replace an include directive by the entries of the file it points to. Returns `false` if
+    /// there is no resolver, so the directive should be kept as it is.
+    fn include(
+        &mut self,
+        kind: IncludeKind,
+        entry: &Entry<'a>,
+    ) -> Result<bool, FoamError<'a>> {
+        if self.context.and_then(Context::resolver).is_none() {
+            return Ok(false);
+        }
+
+        let path = match entry.values.as_slice() {
+            [Foam::Value(path) | Foam::String(path)] => *path,
+            _ => {
+                return Err(FoamError::InvalidDirective {
+                    directive: entry.key,
+                    location: entry.location.clone(),
+                })
+            }
+        };
+        let expanded = self.environment_expand(path);
+        let (name, included) = match self.load(kind, path, &expanded, entry)? {
+            Some(loaded) => loaded,
+            None if kind == IncludeKind::IfPresent => return Ok(true),
+            None => {
+                return Err(FoamError::IncludeNotFound {
+                    path,
+                    location: entry.location.clone(),
+                })
+            }
+        };
+
+        self.files.push(name);
+        let result = self.entries(&included);
+        let name = self.files.pop().expect("current file");
+        result.map_err(|error| FoamError::InInclude {
+            file: name,
+            location: entry.location.clone(),
+            error: Box::new(error),
+        })?;
+        Ok(true)
+    }
+
+    /// Replace the environment variables in a text, if there is a context.
+    fn environment_expand(&self, text: &str) -> String {
+        match self.context {
+            Some(context) => context.environment().expand(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Find, read and parse a file requested by `entry`, returning its name and its entries.
+    /// `path` is the path as it appears in the content and `expanded` the one after replacing the
+    /// environment variables. Returns `None` if there is no resolver or the file doesn't exist.
+    fn load(
+        &self,
+        kind: IncludeKind,
+        path: &'a str,
+        expanded: &str,
+        entry: &Entry<'a>,
+    ) -> Result<Option<(String, Dictionary<'a>)>, FoamError<'a>> {
+        let Some(context) = self.context else {
+            return Ok(None);
+        };
+        let Some(resolver) = context.resolver() else {
+            return Ok(None);
+        };
+
+        let parent = self.files.last().map(String::as_str);
+        let source = match resolver.resolve(kind, expanded, parent) {
+            Ok(Some(source)) => source,
+            Ok(None) => return Ok(None),
+            Err(error) => {
+                return Err(FoamError::IncludeFailed {
+                    path,
+                    reason: error.to_string(),
+                    location: entry.location.clone(),
+                })
+            }
+        };
+        if self.files.contains(&source.name) {
+            let mut chain = self.files.clone();
+            chain.push(source.name);
+            return Err(FoamError::IncludeCycle {
+                chain,
+                location: entry.location.clone(),
+            });
+        }
+
+        let content = context.keep(source.content);
+        match Foam::parse(content) {
+            Ok(Foam::Dictionary(included)) => Ok(Some((source.name, included))),
+            Ok(_) => {
+                unreachable!("the root of the content is always a dictionary")
+            }
+            Err(error) => Err(FoamError::InInclude {
+                file: source.name,
+                location: entry.location.clone(),
+                error: Box::new(error),
+            }),
+        }
+    }
+
+    /// Expand a file pointed by a macro (`$file!a`), returning its entries. Returns `None` if
+    /// there is no resolver or the file doesn't exist.
+    fn other_file(
+        &self,
+        path: &'a str,
+        entry: &Entry<'a>,
+    ) -> Result<Option<Dictionary<'a>>, FoamError<'a>> {
+        // The `$` of the macro is also the `$` of a variable in `$FOAM_CASE/file!a`.
+        let first = path.split('/').next().unwrap_or_default();
+        let expanded = match self.context {
+            Some(context) if context.environment().get(first).is_some() => {
+                self.environment_expand(&format!("${}", path))
+            }
+            _ => path.to_string(),
+        };
+        let Some((name, dict)) =
+            self.load(IncludeKind::File, path, &expanded, entry)?
+        else {
+            return Ok(None);
+        };
+
+        let mut files = self.files.clone();
+        files.push(name.clone());
+        let mut expander = Expander {
+            context: self.context,
+            files,
+            ..Expander::default()
+        };
+        expander
+            .dict(&dict)
+            .map(Some)
+            .map_err(|error| FoamError::InInclude {
+                file: name,
+                location: entry.location.clone(),
+                error: Box::new(error),
+            })
+    }
+
+    /// Copy all the entries of the dictionary a merge entry (`$dict;`) points to into the current
+    /// dictionary.
+    fn merge(&mut self, entry: &Entry<'a>) -> Result<(), FoamError<'a>> {
+        let values = self.resolve(entry.key, entry)?;
+        let Some(Foam::Dictionary(source)) = values.first() else {
+            return Err(FoamError::InvalidMerge {
+                reference: entry.key,
+                location: entry.location.clone(),
+            });
+        };
+
+        let scope = self.scopes.last_mut().expect("current scope");
+        for merged in source.entries() {
+            scope.push(Entry {
+                merged_from: Some(entry.key),
+                ..merged.clone()
+            });
+        }
+        Ok(())
+    }
+
+    /// Expand a list of values, part of `entry`.
+    fn values(
+        &mut self,
+        values: &[Foam<'a>],
+        entry: &Entry<'a>,
+    ) -> Result<Vec<Foam<'a>>, FoamError<'a>> {
+        let mut result = Vec::with_capacity(values.len());
+        let mut values = values.iter();
+        while let Some(value) = values.next() {
+            match value {
+                Foam::Value(directive @ ("#calc" | "#eval")) => {
+                    let expression = match values.next() {
+                        Some(Foam::String(expression))
+                        | Some(Foam::Verbatim(expression)) => *expression,
+                        _ => {
+                            return Err(FoamError::InvalidDirective {
+                                directive,
+                                location: entry.location.clone(),
+                            })
+                        }
+                    };
+                    result.push(self.calc(expression, entry)?);
+                }
+                Foam::Value(text) if text.starts_with('$') => {
+                    result.extend(self.resolve(text, entry)?)
+                }
+                Foam::Dictionary(inner) => {
+                    result.push(Foam::Dictionary(self.dict(inner)?))
+                }
+                Foam::List(items) => {
+                    result.push(Foam::List(self.values(items, entry)?))
+                }
+                _ => result.push(value.clone()),
+            }
+        }
+        Ok(result)
+    }
+
+    /// Replace a `#calc` or `#eval` directive by the result of its expression.
+    fn calc(
+        &mut self,
+        expression: &'a str,
+        entry: &Entry<'a>,
+    ) -> Result<Foam<'a>, FoamError<'a>> {
+        let value = |number: f64| Foam::Calculated(calc::format(number));
+        match self.evaluate(expression, entry)? {
+            Number::Scalar(number) => Ok(value(number)),
+            Number::Vector(vector) => {
+                Ok(Foam::List(vector.iter().map(|c| value(*c)).collect()))
+            }
+        }
+    }
+
+    /// Evaluate an expression, part of `entry`.
+    fn evaluate(
+        &mut self,
+        expression: &'a str,
+        entry: &Entry<'a>,
+    ) -> Result<Number, FoamError<'a>> {
+        let invalid = |reason| FoamError::InvalidExpression {
+            expression,
+            reason,
+            location: entry.location.clone(),
+        };
+        let lookup = |reference| {
+            let number = |value: &Foam<'a>| {
+                value.as_value().ok().and_then(|text| text.parse().ok())
+            };
+            let found = match self.resolve(reference, entry)?.as_slice() {
+                [Foam::List(items)] => match items.as_slice() {
+                    [x, y, z] => number(x)
+                        .zip(number(y))
+                        .zip(number(z))
+                        .map(|((x, y), z)| Number::Vector([x, y, z])),
+                    _ => None,
+                },
+                [value] => number(value).map(Number::Scalar),
+                _ => None,
+            };
+            found.ok_or_else(|| {
+                invalid(format!("{} is not a number or a vector", reference))
+            })
+        };
+        calc::evaluate(expression, lookup, invalid)
+    }
+
+    /// Check the condition of a conditional block. `#if` expects a switch (like `true` or `off`),
+    /// a number or an expression (`#calc "..."`); `#ifeq` compares two words or numbers.
+    fn condition(
+        &mut self,
+        conditional: &Conditional<'a>,
+        entry: &Entry<'a>,
+    ) -> Result<bool, FoamError<'a>> {
+        let invalid = || FoamError::InvalidDirective {
+            directive: conditional.directive,
+            location: entry.location.clone(),
+        };
+        match (conditional.directive, conditional.condition.as_slice()) {
+            (
+                "#if",
+                [Foam::Value("#calc" | "#eval"), Foam::String(expression) | Foam::Verbatim(expression)],
+            ) => match self.evaluate(expression, entry)? {
+                Number::Scalar(value) => Ok(value != 0.0),
+                Number::Vector(_) => Err(FoamError::InvalidExpression {
+                    expression,
+                    reason: "vectors can't be used as conditions".into(),
+                    location: entry.location.clone(),
+                }),
+            },
+            ("#if", [value]) => {
+                let text = self.word(value, entry)?.ok_or_else(invalid)?;
+                switch(&text)
+                    .or_else(|| text.parse::<f64>().ok().map(|n| n != 0.0))
+                    .ok_or_else(invalid)
+            }
+            ("#ifeq", [left, right]) => {
+                let left = self.word(left, entry)?.ok_or_else(invalid)?;
+                let right = self.word(right, entry)?.ok_or_else(invalid)?;
+                Ok(match (left.parse::<f64>(), right.parse::<f64>()) {
+                    (Ok(left), Ok(right)) => left == right,
+                    _ => left == right,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The text of a value in a condition, expanding it if it's a macro. Returns `None` if the
+    /// value (or what the macro points to) is not a single word or string.
+    fn word(
+        &mut self,
+        value: &Foam<'a>,
+        entry: &Entry<'a>,
+    ) -> Result<Option<Cow<'a, str>>, FoamError<'a>> {
+        match value {
+            Foam::Value(text) if text.starts_with('$') => {
+                match self.resolve(text, entry)?.as_mut_slice() {
+                    [Foam::Value(text) | Foam::String(text)] => {
+                        Ok(Some(Cow::Borrowed(text)))
+                    }
+                    [Foam::Calculated(text)] => {
+                        Ok(Some(Cow::Owned(std::mem::take(text))))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            Foam::Value(text) | Foam::String(text) => {
+                Ok(Some(Cow::Borrowed(text)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Find the values a macro points to.
+    fn resolve(
+        &mut self,
+        reference: &'a str,
+        entry: &Entry<'a>,
+    ) -> Result<Vec<Foam<'a>>, FoamError<'a>> {
+        let unresolved = || FoamError::UnresolvedReference {
+            reference,
+            location: entry.location.clone(),
+        };
+
+        let name = &reference[1..];
+        let name = match name
+            .strip_prefix('{')
+            .and_then(|name| name.strip_suffix('}'))
+        {
+            // The name is another macro, which must be expanded to a single word.
+            Some(inner) if inner.starts_with('$') => {
+                match self.resolve(inner, entry)?.as_slice() {
+                    [Foam::Value(name)] => *name,
+                    _ => return Err(unresolved()),
+                }
+            }
+            Some(inner) => inner,
+            None => name,
+        };
+
+        let other;
+        let (scopes, mut scope, path, mut recursive) = match name
+            .split_once('!')
+        {
+            Some(("", path)) => (self.scopes.as_slice(), 0, path, false),
+            Some((file, path)) => {
+                other = self.other_file(file, entry)?.ok_or_else(unresolved)?;
+                (std::slice::from_ref(&other), 0, path, false)
+            }
+            None => (self.scopes.as_slice(), self.scopes.len() - 1, name, true),
+        };
+
+        let mut segments = path.split('/').peekable();
+        while segments.peek() == Some(&"..") {
+            segments.next();
+            scope = scope.checked_sub(1).ok_or_else(unresolved)?;
+            recursive = false;
+        }
+
+        let first = segments
+            .next()
+            .filter(|first| !first.is_empty())
+            .ok_or_else(unresolved)?;
+        let found = if recursive {
+            scopes[..=scope]
+                .iter()
+                .rev()
+                .find_map(|dict| dict.get(first))
+        } else {
+            scopes[scope].get(first)
+        };
+        let mut values = match found {
+            Some(values) => values,
+            None if self.expanding.contains(&first) => {
+                return Err(FoamError::CyclicReference {
+                    reference,
+                    location: entry.location.clone(),
+                })
+            }
+            // Names that are not in the dictionaries can be environment variables.
+            None if recursive && segments.peek().is_none() => {
+                return match self.context.and_then(|context| {
+                    let value = context.environment().get(first)?;
+                    Some(Foam::Value(context.keep(value)))
+                }) {
+                    Some(value) => Ok(vec![value]),
+                    None => Err(unresolved()),
+                };
+            }
+            None => return Err(unresolved()),
+        };
+
+        for segment in segments {
+            values = values
+                .iter()
+                .find_map(|value| match value {
+                    Foam::Dictionary(inner) => inner.get(segment),
+                    _ => None,
+                })
+                .ok_or_else(unresolved)?;
+        }
+        Ok(values.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::Context;
+    use crate::Dictionary;
+    use crate::Environment;
+    use crate::Foam;
+    use crate::FoamError;
+    use crate::IncludeKind;
+    use crate::Location;
+    use crate::Resolver;
+    use crate::Source;
+
+    /// A context with in-memory files.
+    fn files(files: &[(&str, &str)]) -> Context {
+        let files = files
+            .iter()
+            .map(|(name, content)| (name.to_string(), content.to_string()))
+            .collect::<HashMap<_, _>>();
+        Context::new().with_resolver(files)
+    }
+
+    #[test]
+    fn simple() {
+        let root = Foam::parse("a 10; b $a;").unwrap();
+        let expected = Foam::Dictionary(Dictionary::from([
+            ("a", vec![Foam::Value("10")]),
+            ("b", vec![Foam::Value("10")]),
+        ]));
+        assert_eq!(root.expand(), Ok(expected));
+    }
+
+    #[test]
+    fn multiple_values_and_lists() {
+        let root =
+            Foam::parse("a 1 2; b ( 0 $a 3 ); c { x 1; } d $c;").unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(
+            expanded.get("b"),
+            Ok([Foam::List(vec![
+                Foam::Value("0"),
+                Foam::Value("1"),
+                Foam::Value("2"),
+                Foam::Value("3"),
+            ])]
+            .as_slice())
+        );
+        assert_eq!(expanded.get("d"), expanded.get("c"));
+    }
+
+    #[test]
+    fn format_example() {
+        let root = Foam::parse(
+            "a 10;
+b a;
+c ${${b}};
+
+subdictA
+{
+    a 20;
+}
+
+subdictB
+{
+    b $../subdictA/a;
+    c $subdictA/a;
+    d $a;
+
+    subsubdict
+    {
+        b $!a;
+        c ${a};
+    }
+}
+",
+        )
+        .unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(expanded.get_first_value("c"), Ok("10"));
+
+        let subdict_b = expanded.get_first("subdictB").unwrap();
+        assert_eq!(subdict_b.get_first_value("b"), Ok("20"));
+        assert_eq!(subdict_b.get_first_value("c"), Ok("20"));
+        assert_eq!(subdict_b.get_first_value("d"), Ok("10"));
+
+        let subsubdict = subdict_b.get_first("subsubdict").unwrap();
+        assert_eq!(subsubdict.get_first_value("b"), Ok("10"));
+        assert_eq!(subsubdict.get_first_value("c"), Ok("10"));
+    }
+
+    #[test]
+    fn closest_scope_wins() {
+        let root = Foam::parse("a 1; inner { a 2; b $a; } c $a;").unwrap();
+        let expanded = root.expand().unwrap();
+        let inner = expanded.get_first("inner").unwrap();
+        assert_eq!(inner.get_first_value("b"), Ok("2"));
+        assert_eq!(expanded.get_first_value("c"), Ok("1"));
+    }
+
+    #[test]
+    fn uses_previous_definition() {
+        let root = Foam::parse("a 1; b $a; a 2; c $a;").unwrap();
+        let expanded = root.expand().unwrap();
+        let dict = expanded.as_dict().unwrap();
+        assert_eq!(dict.get("b"), Some([Foam::Value("1")].as_slice()));
+        assert_eq!(dict.get("c"), Some([Foam::Value("2")].as_slice()));
+    }
+
+    #[test]
+    fn unresolved() {
+        let root = Foam::parse("a 1;\ninner\n{\n    b $../../a;\n}").unwrap();
+        assert_eq!(
+            root.expand(),
+            Err(FoamError::UnresolvedReference {
+                reference: "$../../a",
+                location: Some(Location {
+                    span: 17..18,
+                    line: 4,
+                    column: 5
+                })
+            })
+        );
+
+        let root = Foam::parse("a 1; b $a/c;").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::UnresolvedReference {
+                reference: "$a/c",
+                ..
+            })
+        ));
+
+        let root = Foam::parse("a 1; b $otherFile!a;").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::UnresolvedReference {
+                reference: "$otherFile!a",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn cyclic() {
+        let root = Foam::parse("a $a;").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::CyclicReference {
+                reference: "$a",
+                ..
+            })
+        ));
+
+        let root = Foam::parse("p { q { r $p/x; } }").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::CyclicReference {
+                reference: "$p/x",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn merge() {
+        let root = Foam::parse(
+            "p
+{
+    solver          PCG;
+    preconditioner  DIC;
+    tolerance       1e-6;
+    relTol          0.05;
+}
+pFinal
+{
+    $p;
+    relTol          0;
+    maxIter         $tolerance;
+}
+",
+        )
+        .unwrap();
+        let expanded = root.expand().unwrap();
+        let p_final = expanded.get_first_dict("pFinal").unwrap();
+        assert_eq!(
+            p_final.keys().collect::<Vec<_>>(),
+            vec!["solver", "preconditioner", "tolerance", "relTol", "maxIter"]
+        );
+        assert_eq!(p_final.get("relTol"), Some([Foam::Value("0")].as_slice()));
+        assert_eq!(
+            p_final.get("maxIter"),
+            Some([Foam::Value("1e-6")].as_slice())
+        );
+        assert!(p_final.is_merged("solver"));
+        assert!(p_final.is_merged("tolerance"));
+        assert!(!p_final.is_merged("relTol"));
+        assert!(!p_final.is_merged("maxIter"));
+        assert!(!p_final.is_merged("missing"));
+        assert_eq!(
+            p_final.get_entry("solver").unwrap().merged_from,
+            Some("$p")
+        );
+
+        // The overridden entry is still there.
+        assert_eq!(p_final.get_all("relTol").count(), 2);
+    }
+
+    #[test]
+    fn merge_scoped() {
+        let root = Foam::parse(
+            "defaults { a 1; b 2; }
+solvers { U { $!defaults; b 3; } k { $../../defaults; } }",
+        )
+        .unwrap();
+        let expanded = root.expand().unwrap();
+        let solvers = expanded.get_first("solvers").unwrap();
+        let u = solvers.get_first("U").unwrap();
+        assert_eq!(u.get_first_value("a"), Ok("1"));
+        assert_eq!(u.get_first_value("b"), Ok("3"));
+        let k = solvers.get_first("k").unwrap();
+        assert_eq!(k.get_first_value("b"), Ok("2"));
+    }
+
+    #[test]
+    fn invalid_merge() {
+        let root = Foam::parse("a 1; b { $a; }").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::InvalidMerge {
+                reference: "$a",
+                ..
+            })
+        ));
+
+        let root = Foam::parse("b { $a; }").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::UnresolvedReference {
+                reference: "$a",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn not_macros() {
+        let root = Foam::parse("a 1; b \"$a\"; c #{ $a #};").unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(expanded, root);
+    }
+
+    #[test]
+    fn include() {
+        let context = files(&[("common", "a 1; b 2;")]);
+        let root = Foam::parse("a 0; #include \"common\" b 3; c $a;").unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        let expected = Foam::Dictionary(Dictionary::from([
+            ("a", vec![Foam::Value("0")]),
+            ("a", vec![Foam::Value("1")]),
+            ("b", vec![Foam::Value("2")]),
+            ("b", vec![Foam::Value("3")]),
+            ("c", vec![Foam::Value("1")]),
+        ]));
+        assert_eq!(expanded, expected);
+    }
+
+    #[test]
+    fn include_in_subdict() {
+        let context =
+            files(&[("relVelocity", "relVelocity { type fluxSummary; }")]);
+        let root =
+            Foam::parse(include_str!("../resources/controlDict")).unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        let functions = expanded.get_first("functions").unwrap();
+        let function = functions.get_first("relVelocity").unwrap();
+        assert_eq!(function.get_first_value("type"), Ok("fluxSummary"));
+    }
+
+    #[test]
+    fn nested_includes() {
+        let context = files(&[
+            ("first", "#include \"second\" a $b;"),
+            ("second", "b 2;"),
+        ]);
+        let root = Foam::parse("#includeEtc \"first\"").unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("a"), Ok("2"));
+    }
+
+    #[test]
+    fn without_resolver() {
+        let root = Foam::parse("#include \"common\" a 1;").unwrap();
+        assert_eq!(root.expand_with(&Context::new()), Ok(root.clone()));
+        assert_eq!(root.expand(), Ok(root));
+    }
+
+    #[test]
+    fn include_missing() {
+        let context = files(&[]);
+        let root = Foam::parse("#includeIfPresent \"missing\" a 1;").unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        let expected = Dictionary::from([("a", vec![Foam::Value("1")])]);
+        assert_eq!(expanded, Foam::Dictionary(expected));
+
+        let root = Foam::parse("a 1;\n#include \"missing\"").unwrap();
+        assert_eq!(
+            root.expand_with(&context),
+            Err(FoamError::IncludeNotFound {
+                path: "missing",
+                location: Some(Location {
+                    span: 5..13,
+                    line: 2,
+                    column: 1
+                })
+            })
+        );
+    }
+
+    #[test]
+    fn include_cycle() {
+        let context =
+            files(&[("a", "#include \"b\""), ("b", "#include \"a\"")])
+                .with_name("root");
+        let root = Foam::parse("#include \"a\"").unwrap();
+        let error = root.expand_with(&context).unwrap_err();
+        let FoamError::InInclude { file, error, .. } = error else {
+            panic!("error not in the included file: {:?}", error);
+        };
+        assert_eq!(file, "a");
+        assert!(matches!(
+            *error,
+            FoamError::InInclude { ref file, ref error, .. }
+                if file == "b" && matches!(
+                    **error,
+                    FoamError::IncludeCycle { ref chain, .. }
+                        if chain == &["root", "a", "b", "a"]
+                )
+        ));
+    }
+
+    #[test]
+    fn errors_name_the_include_chain() {
+        let context = files(&[
+            ("first", "#include \"second\""),
+            ("second", "a $missing;"),
+        ]);
+        let root = Foam::parse("#include \"first\"").unwrap();
+        let error = root.expand_with(&context).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "In \"first\": In \"second\": Could not resolve \"$missing\" \
+             (at line 1, column 1)"
+        );
+
+        let context = files(&[("broken", "a ( 1;")]);
+        let root = Foam::parse("#include \"broken\"").unwrap();
+        let error = root.expand_with(&context).unwrap_err();
+        assert!(matches!(
+            error,
+            FoamError::InInclude { ref file, ref error, .. }
+                if file == "broken"
+                    && matches!(**error, FoamError::UnexpectedToken { token: ";", .. })
+        ));
+    }
+
+    #[test]
+    fn other_files() {
+        let context = files(&[("other", "x 1; sub { y $x; }")]);
+        let root = Foam::parse("a $other!sub/y; b $other!x;").unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("a"), Ok("1"));
+        assert_eq!(expanded.get_first_value("b"), Ok("1"));
+
+        let root = Foam::parse("a $missing!x;").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::UnresolvedReference {
+                reference: "$missing!x",
+                ..
+            })
+        ));
+    }
+
+    /// A resolver that only allows relative paths.
+    struct Sandbox(HashMap<String, String>);
+
+    impl Resolver for Sandbox {
+        fn resolve(
+            &self,
+            kind: IncludeKind,
+            path: &str,
+            parent: Option<&str>,
+        ) -> std::io::Result<Option<Source>> {
+            if path.starts_with('/') {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "absolute paths are not allowed",
+                ));
+            }
+            self.0.resolve(kind, path, parent)
+        }
+    }
+
+    #[test]
+    fn custom_resolver() {
+        let files = HashMap::from([("/etc/passwd".to_string(), String::new())]);
+        let context = Context::new().with_resolver(Sandbox(files));
+        let root = Foam::parse("#include \"/etc/passwd\"").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::IncludeFailed {
+                path: "/etc/passwd",
+                ref reason,
+                ..
+            }) if reason == "absolute paths are not allowed"
+        ));
+    }
+
+    #[test]
+    fn remove() {
+        let root = Foam::parse(
+            "a 1; b 2; inlet1 3; inlet2 4; outlet 5;
+            #remove a
+            #remove (\"inlet.*\" outlet)
+            a 6;",
+        )
+        .unwrap();
+        let expected = Foam::Dictionary(Dictionary::from([
+            ("b", vec![Foam::Value("2")]),
+            ("a", vec![Foam::Value("6")]),
+        ]));
+        assert_eq!(root.expand(), Ok(expected));
+    }
+
+    #[test]
+    fn remove_included() {
+        let context = files(&[("defaults", "solver PCG; tolerance 1e-6;")]);
+        let root = Foam::parse(
+            "p { #include \"defaults\" #remove tolerance relTol 0; }",
+        )
+        .unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        let expected = Dictionary::from([
+            ("solver", vec![Foam::Value("PCG")]),
+            ("relTol", vec![Foam::Value("0")]),
+        ]);
+        assert_eq!(expanded.get_first_dict("p"), Ok(&expected));
+    }
+
+    #[test]
+    fn remove_patterns_match_whole_keys() {
+        let root =
+            Foam::parse("inlet 1; inletWall 2; #remove \"inlet\"").unwrap();
+        let expected =
+            Dictionary::from([("inletWall", vec![Foam::Value("2")])]);
+        assert_eq!(root.expand(), Ok(Foam::Dictionary(expected)));
+    }
+
+    #[test]
+    fn remove_invalid() {
+        let root = Foam::parse("a 1; #remove \"(a\"").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::InvalidPattern { pattern: "(a", .. })
+        ));
+
+        let root = Foam::parse("a 1; #remove ((a))").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::InvalidDirective {
+                directive: "#remove",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn calc() {
+        let root = Foam::parse(
+            "halfAngle 45.0;
+            radius 0.5;
+            radHalfAngle #calc \"degToRad($halfAngle)\";
+            y #calc \"$radius*sin($radHalfAngle)\";
+            z #calc \"$radius*cos($radHalfAngle)\";
+            vertices ( (0 0 0) (0 #calc \"-$y\" $z) );
+            sub { double #eval #{ 2 * ${../radius} #}; }",
+        )
+        .unwrap();
+        let context = Context::new();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(
+            expanded.get_first_value("radHalfAngle"),
+            Ok("0.7853981633974483")
+        );
+        assert_eq!(expanded.get_first_value("y"), Ok("0.35355339059327373"));
+        assert_eq!(
+            expanded.get_first_list("vertices").unwrap()[1],
+            Foam::List(vec![
+                Foam::Value("0"),
+                Foam::Calculated("-0.35355339059327373".into()),
+                Foam::Calculated("0.3535533905932738".into()),
+            ])
+        );
+        let sub = expanded.get_first("sub").unwrap();
+        assert_eq!(sub.get_first_value("double"), Ok("1"));
+    }
+
+    #[test]
+    fn calc_vectors() {
+        let root = Foam::parse(
+            "origin (1 2 3); moved #calc \"$origin + vector(1, 1, 1)\";",
+        )
+        .unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(
+            expanded.get("moved"),
+            Ok([Foam::List(vec![
+                Foam::Calculated("2".into()),
+                Foam::Calculated("3".into()),
+                Foam::Calculated("4".into()),
+            ])]
+            .as_slice())
+        );
+    }
+
+    #[test]
+    fn calc_without_context() {
+        let root = Foam::parse("a 1; b #calc \"$a + 1\";").unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(
+            expanded.get("b"),
+            Ok([Foam::Calculated("2".into())].as_slice())
+        );
+        assert_eq!(expanded.to_string(), "a   1 ;\nb   2 ;\n");
+
+        let root = Foam::parse("b #calc \"1 + 1\";\n#ifeq $b 2\nc 1;\n#endif")
+            .unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(expanded.get_first_value("c"), Ok("1"));
+    }
+
+    #[test]
+    fn calc_divides_floating_point_numbers() {
+        // OpenFOAM compiles the expression as C++, where `7/2` is an integer division and gives
+        // 3; here all numbers are floating point.
+        let root = Foam::parse("a #calc \"7/2\"; b #calc \"7%2\";").unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(expanded.get_f64("a"), Ok(3.5));
+        assert_eq!(expanded.get_f64("b"), Ok(1.0));
+    }
+
+    #[test]
+    fn calc_in_conditions_and_values() {
+        let root = Foam::parse(
+            "#if #calc \"1 + 1\"\na 1;\n#endif\nb #calc \"1 + 1\";",
+        )
+        .unwrap();
+        let context = Context::new();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("a"), Ok("1"));
+        assert_eq!(expanded.get_first_value("b"), Ok("2"));
+    }
+
+    #[test]
+    fn calc_conditions_without_context() {
+        let root = Foam::parse(
+            "#if #calc \"1 < 2\"\na 1;\n#else\na 2;\n#endif\n#if #eval \"2 < 1\"\nb 1;\n#endif",
+        )
+        .unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(expanded.get_first_value("a"), Ok("1"));
+        assert!(expanded.get("b").is_err());
+    }
+
+    #[test]
+    fn calc_errors() {
+        let context = Context::new();
+        let root = Foam::parse("a #calc \"1 +\";").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::InvalidExpression {
+                expression: "1 +",
+                ref reason,
+                ..
+            }) if reason == "unexpected end of the expression"
+        ));
+
+        let root = Foam::parse("a word; b #calc \"$a * 2\";").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::InvalidExpression { ref reason, .. })
+                if reason == "$a is not a number or a vector"
+        ));
+
+        let root = Foam::parse("b #calc \"$a * 2\";").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::UnresolvedReference {
+                reference: "$a",
+                ..
+            })
+        ));
+
+        let root = Foam::parse("b #calc;").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::InvalidDirective {
+                directive: "#calc",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn conditionals() {
+        let root = Foam::parse(
+            "angle 65;
+            laplacianSchemes
+            {
+            #if #calc \"${angle} < 75\"
+                default  Gauss linear corrected;
+            #else
+                default  Gauss linear limited corrected 0.5;
+            #endif
+            }
+            #if $angle
+            nonZero yes;
+            #endif
+            #if off
+            never yes;
+            #endif",
+        )
+        .unwrap();
+        let expected = Foam::Dictionary(Dictionary::from([
+            ("angle", vec![Foam::Value("65")]),
+            (
+                "laplacianSchemes",
+                vec![Foam::Dictionary(Dictionary::from([(
+                    "default",
+                    vec![
+                        Foam::Value("Gauss"),
+                        Foam::Value("linear"),
+                        Foam::Value("corrected"),
+                    ],
+                )]))],
+            ),
+            ("nonZero", vec![Foam::Value("yes")]),
+        ]));
+        assert_eq!(root.expand(), Ok(expected));
+    }
+
+    #[test]
+    fn ifeq() {
+        let root = Foam::parse(
+            "a 1.0; b word;
+            #ifeq $a 1
+                numbers equal;
+            #endif
+            #ifeq $b \"other\"
+                words equal;
+            #else
+                words different;
+            #endif",
+        )
+        .unwrap();
+        let expanded = root.expand().unwrap();
+        assert_eq!(expanded.get_first_value("numbers"), Ok("equal"));
+        assert_eq!(expanded.get_first_value("words"), Ok("different"));
+    }
+
+    #[test]
+    fn nested_conditionals() {
+        let root = Foam::parse(
+            "#if true
+                #if false
+                    a 1;
+                #else
+                    a 2;
+                #endif
+                b $a;
+            #endif",
+        )
+        .unwrap();
+        let expected = Dictionary::from([
+            ("a", vec![Foam::Value("2")]),
+            ("b", vec![Foam::Value("2")]),
+        ]);
+        assert_eq!(root.expand(), Ok(Foam::Dictionary(expected)));
+    }
+
+    #[test]
+    fn invalid_conditions() {
+        let root = Foam::parse("#if maybe\na 1;\n#endif").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::InvalidDirective {
+                directive: "#if",
+                ..
+            })
+        ));
+
+        let root = Foam::parse("#ifeq a\na 1;\n#endif").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::InvalidDirective {
+                directive: "#ifeq",
+                ..
+            })
+        ));
+
+        let root = Foam::parse("#if $missing\na 1;\n#endif").unwrap();
+        assert!(matches!(
+            root.expand(),
+            Err(FoamError::UnresolvedReference {
+                reference: "$missing",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn environment() {
+        let environment = Environment::new()
+            .with("FOAM_CASE", "/run/pitzDaily")
+            .with("HOME", "/home/user");
+        let context = Context::new().with_environment(environment);
+        let root = Foam::parse(
+            "case $FOAM_CASE; name ${FOAM_CASENAME}; HOME here; home $HOME;
+            sub { home $HOME; }",
+        )
+        .unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("case"), Ok("/run/pitzDaily"));
+        assert_eq!(expanded.get_first_value("name"), Ok("pitzDaily"));
+        // Entries come before the environment.
+        assert_eq!(expanded.get_first_value("home"), Ok("here"));
+        let sub = expanded.get_first("sub").unwrap();
+        assert_eq!(sub.get_first_value("home"), Ok("here"));
+    }
+
+    #[test]
+    fn undefined_environment() {
+        let context = Context::new();
+        let root = Foam::parse("a $FOAM_RUN;").unwrap();
+        assert!(matches!(
+            root.expand_with(&context),
+            Err(FoamError::UnresolvedReference {
+                reference: "$FOAM_RUN",
+                ..
+            })
+        ));
+
+        let context = files(&[("$FOAM_RUN/common", "a 1;")]);
+        let root = Foam::parse("#include \"$FOAM_RUN/common\"").unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("a"), Ok("1"));
+    }
+
+    #[test]
+    fn environment_in_paths() {
+        let context = files(&[
+            ("/run/pitzDaily/0/U", "a 1;"),
+            ("/case/otherFile", "b 2;"),
+        ])
+        .with_environment(
+            Environment::new()
+                .with("FOAM_RUN", "/run")
+                .with("FOAM_CASE", "/case"),
+        );
+        let root = Foam::parse(
+            "#include \"$FOAM_RUN/pitzDaily/0/U\"
+            b $FOAM_CASE/otherFile!b;",
+        )
+        .unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("a"), Ok("1"));
+        assert_eq!(expanded.get_first_value("b"), Ok("2"));
+    }
+
+    #[test]
+    fn environment_in_expressions() {
+        let environment = Environment::from_iter([("ANGLE", "30")]);
+        let context = Context::new().with_environment(environment);
+        let root = Foam::parse(
+            "#if #calc \"$ANGLE < 45\"
+            small yes;
+            #endif
+            half #calc \"$ANGLE / 2\";",
+        )
+        .unwrap();
+        let expanded = root.expand_with(&context).unwrap();
+        assert_eq!(expanded.get_first_value("small"), Ok("yes"));
+        assert_eq!(expanded.get_first_value("half"), Ok("15"));
+    }
+
+    #[test]
+    fn code_stream_is_inert() {
+        let root = Foam::parse(
+            "mass 1; inertia #codeStream { code #{ os << $mass; #}; codeLibs $mass; };",
+        )
+        .unwrap();
+        let context = Context::new();
+        assert_eq!(root.expand_with(&context), Ok(root));
+    }
+}
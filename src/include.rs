@@ -0,0 +1,248 @@
+//! Finds the files requested by `#include` directives.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::Context;
+use crate::Environment;
+use crate::FileSystem;
+use crate::IncludeKind;
+use crate::Resolver;
+use crate::Source;
+
+impl Context {
+    /// Create an empty context. Without a resolver, include directives are kept in the tree as
+    /// they are.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `resolver` to find included files.
+    pub fn with_resolver(mut self, resolver: impl Resolver + 'static) -> Self {
+        self.resolver = Some(Box::new(resolver));
+        self
+    }
+
+    /// Name of the file being expanded. Relative includes in it are relative to this name, and
+    /// it's part of the chain when looking for files including each other.
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Use `environment` for environment variables (like `$FOAM_CASE`), in values and in the
+    /// paths of included files. Without it, no variables are defined.
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// The resolver, if there is one.
+    pub(crate) fn resolver(&self) -> Option<&dyn Resolver> {
+        self.resolver.as_deref()
+    }
+
+    /// Name of the file being expanded, if known.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Values of the environment variables.
+    pub(crate) fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// Keep the content of a loaded file for as long as the context lives.
+    pub(crate) fn keep(&self, content: String) -> &str {
+        self.files.alloc(content)
+    }
+}
+
+impl IncludeKind {
+    /// The kind of include of a directive, if the directive is an include.
+    pub(crate) fn from_directive(directive: &str) -> Option<Self> {
+        match directive {
+            "#include" => Some(IncludeKind::File),
+            "#includeIfPresent" => Some(IncludeKind::IfPresent),
+            "#includeEtc" => Some(IncludeKind::Etc),
+            "#includeFunc" => Some(IncludeKind::Func),
+            _ => None,
+        }
+    }
+}
+
+impl FileSystem {
+    /// Create a resolver with no case or `etc` directories.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory of the case. Used for relative paths in the root file and for `#includeFunc`.
+    pub fn with_case(mut self, case: impl Into<PathBuf>) -> Self {
+        self.case = Some(case.into());
+        self
+    }
+
+    /// The OpenFOAM `etc` directory, used by `#includeEtc` and `#includeFunc`.
+    pub fn with_etc(mut self, etc: impl Into<PathBuf>) -> Self {
+        self.etc = Some(etc.into());
+        self
+    }
+
+    /// Paths where the file could be, in the order they should be tried.
+    fn candidates(
+        &self,
+        kind: IncludeKind,
+        path: &str,
+        parent: Option<&str>,
+    ) -> Vec<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            return vec![path.to_path_buf()];
+        }
+
+        match kind {
+            IncludeKind::File | IncludeKind::IfPresent => {
+                let base = match parent {
+                    Some(parent) => Path::new(parent)
+                        .parent()
+                        .map(Path::to_path_buf)
+                        .unwrap_or_default(),
+                    None => self.case.clone().unwrap_or_default(),
+                };
+                vec![base.join(path)]
+            }
+            IncludeKind::Etc => {
+                self.etc.iter().map(|etc| etc.join(path)).collect()
+            }
+            IncludeKind::Func => self
+                .case
+                .iter()
+                .map(|case| case.join("system").join(path))
+                .chain(self.etc.iter().map(|etc| etc.join(path)))
+                .collect(),
+        }
+    }
+}
+
+impl Resolver for FileSystem {
+    fn resolve(
+        &self,
+        kind: IncludeKind,
+        path: &str,
+        parent: Option<&str>,
+    ) -> std::io::Result<Option<Source>> {
+        for candidate in self.candidates(kind, path, parent) {
+            match std::fs::read_to_string(&candidate) {
+                Ok(content) => {
+                    return Ok(Some(Source {
+                        name: candidate.display().to_string(),
+                        content,
+                    }))
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    continue
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// In-memory files, mapping the paths (exactly as they appear in the directives) to their
+/// content.
+impl Resolver for HashMap<String, String> {
+    fn resolve(
+        &self,
+        _kind: IncludeKind,
+        path: &str,
+        _parent: Option<&str>,
+    ) -> std::io::Result<Option<Source>> {
+        Ok(self.get(path).map(|content| Source {
+            name: path.to_string(),
+            content: content.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::FileSystem;
+    use crate::IncludeKind;
+
+    #[test]
+    fn relative_to_parent() {
+        let resolver = FileSystem::new().with_case("/case");
+        assert_eq!(
+            resolver.candidates(
+                IncludeKind::File,
+                "initialConditions",
+                Some("/case/0/U")
+            ),
+            vec![PathBuf::from("/case/0/initialConditions")]
+        );
+        assert_eq!(
+            resolver.candidates(IncludeKind::File, "system/common", None),
+            vec![PathBuf::from("/case/system/common")]
+        );
+    }
+
+    #[test]
+    fn absolute() {
+        let resolver = FileSystem::new().with_case("/case");
+        assert_eq!(
+            resolver.candidates(IncludeKind::Etc, "/other/file", None),
+            vec![PathBuf::from("/other/file")]
+        );
+    }
+
+    #[test]
+    fn etc_and_func() {
+        let resolver = FileSystem::new().with_case("/case").with_etc("/etc");
+        assert_eq!(
+            resolver.candidates(
+                IncludeKind::Etc,
+                "caseDicts/setConstraintTypes",
+                None
+            ),
+            vec![PathBuf::from("/etc/caseDicts/setConstraintTypes")]
+        );
+        assert_eq!(
+            resolver.candidates(IncludeKind::Func, "streamlines", None),
+            vec![
+                PathBuf::from("/case/system/streamlines"),
+                PathBuf::from("/etc/streamlines")
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_files() {
+        let dir = std::env::temp_dir()
+            .join(format!("foamparser-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("system")).unwrap();
+        std::fs::write(dir.join("system/common"), "a 1;").unwrap();
+
+        let resolver = FileSystem::new().with_case(&dir);
+        let found = crate::Resolver::resolve(
+            &resolver,
+            IncludeKind::File,
+            "system/common",
+            None,
+        );
+        let missing = crate::Resolver::resolve(
+            &resolver,
+            IncludeKind::IfPresent,
+            "system/missing",
+            None,
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found.unwrap().unwrap().content, "a 1;");
+        assert_eq!(missing.unwrap(), None);
+    }
+}
@@ -1,14 +1,25 @@
 //! Parse a Foam file into a major structure.
 
 use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
 
 mod access;
+mod calc;
+mod cursor;
+mod diagnostic;
+mod dictionary;
+mod dimensioned;
+mod environment;
+mod expand;
+mod include;
 mod output;
 mod parser;
+mod tensor;
 mod tokenizer;
 
 /// The structures inside a Foamfile
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Foam<'a> {
     /// A dictionary (key/value pairs).
     /// The root of a foam documentation is always a dictionary, and the entries at the top level
@@ -29,40 +40,473 @@ pub enum Foam<'a> {
     /// ```
     ///
     /// ... is a dictionary in which the value is another dictionary.
-    Dictionary(HashMap<&'a str, Vec<Foam<'a>>>),
+    Dictionary(Dictionary<'a>),
 
     /// A single value.
     Value(&'a str),
 
+    /// The result of an expression (`#calc` or `#eval`), once the tree is expanded. It works like
+    /// a [`Foam::Value`], but owns its text, since the text doesn't appear in the content.
+    Calculated(String),
+
+    /// A quoted string. The content is kept exactly as it appears between the quotes, including
+    /// any escaped characters (like `\"`).
+    String(&'a str),
+
+    /// A verbatim block, `#{ ... #}`, usually with C++ code. The content is kept exactly as it
+    /// appears between the markers, including new lines.
+    Verbatim(&'a str),
+
     /// A list.
     List(Vec<Foam<'a>>),
 
     /// A dimensional list.
-    /// This works kinda like Lists, but are used for dimensional content (for whatever that means).
+    /// This works kinda like Lists, but are used for dimensional content, like
+    /// `[0 2 -1 0 0 0 0]` or, in the named units form, `[m^2 s^-1]`.
     Dimension(Vec<&'a str>),
+
+    /// A `#codeStream` directive, with its dictionary. The code is kept as it is, so it can be
+    /// inspected (see [`Dictionary::coded`]) and written back, but it's never compiled or
+    /// executed.
+    CodeStream(Dictionary<'a>),
+
+    /// A conditional block (`#if`/`#ifeq` ... `#else` ... `#endif`). Both branches are kept; the
+    /// one that is used is selected when the tree is expanded.
+    Conditional(Conditional<'a>),
+}
+
+/// A conditional block inside a dictionary, like
+///
+/// ```cpp
+/// #ifeq ${FOAM_APPLICATION} simpleFoam
+///     default steadyState;
+/// #else
+///     default Euler;
+/// #endif
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conditional<'a> {
+    /// The directive, `#if` or `#ifeq`.
+    pub directive: &'a str,
+
+    /// The condition, everything in the line after the directive.
+    pub condition: Vec<Foam<'a>>,
+
+    /// Entries used when the condition is true.
+    pub then: Dictionary<'a>,
+
+    /// Entries used when the condition is false, if there is an `#else`.
+    pub otherwise: Option<Dictionary<'a>>,
+}
+
+/// The entries of a [`Foam::Dictionary`].
+///
+/// Entries are kept in the same order they appear in the content (OpenFOAM gives meaning to the
+/// order of entries, and keeping it means the output looks like the input), but finding an entry
+/// by its key doesn't require going through all of them.
+///
+/// A key can appear more than once; all occurrences are kept, but -- like OpenFOAM -- the last one
+/// is the one that counts. The exception is when both are dictionaries: like OpenFOAM's default
+/// `#inputMode merge`, the entries of the last one are merged into the previous one, recursively.
+#[derive(Clone, Default)]
+pub struct Dictionary<'a> {
+    /// The entries, in order.
+    entries: Vec<Entry<'a>>,
+
+    /// Positions of each key in `entries`.
+    index: HashMap<&'a str, Vec<usize>>,
+
+    /// Positions of the entries with quoted keys in `entries`, in order, with their keys compiled
+    /// as regular expressions.
+    patterns: Vec<(usize, regex::Regex)>,
+
+    /// Entries that repeat a dictionary, by their position in `entries`, with the dictionaries of
+    /// all the occurrences merged. These are the ones lookups return.
+    merged: HashMap<usize, Entry<'a>>,
+}
+
+/// The entries with code in a dictionary, like the ones of a `#codeStream` directive or of coded
+/// boundary conditions and function objects (e.g., `codedFixedValue`). The code is the content of
+/// the verbatim blocks (`#{ ... #}`) or strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Coded<'a> {
+    /// The code itself (`code`).
+    pub code: Option<&'a str>,
+
+    /// Additional C++ `#include` statements (`codeInclude`).
+    pub code_include: Option<&'a str>,
+
+    /// Extra compilation flags (`codeOptions`).
+    pub code_options: Option<&'a str>,
+
+    /// Extra libraries to link with (`codeLibs`).
+    pub code_libs: Option<&'a str>,
+}
+
+/// A vector, written as a list of its 3 components: `(1 0 0)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vector(pub [f64; 3]);
+
+/// A tensor, written as a list of its 9 components, row by row: `(1 0 0 0 1 0 0 0 1)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tensor(pub [f64; 9]);
+
+/// A symmetric tensor, written as a list of the 6 components of its upper triangle (`xx xy xz
+/// yy yz zz`): `(1 0 0 1 0 1)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SymmTensor(pub [f64; 6]);
+
+/// A spherical tensor (a multiple of the identity), written as a list with its single
+/// component: `(1)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SphericalTensor(pub [f64; 1]);
+
+/// The dimensions of a physical quantity, as the exponents of the SI base units in OpenFOAM's
+/// order: mass (kg), length (m), time (s), temperature (K), quantity (mol), current (A) and
+/// luminous intensity (cd).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Dimensions(pub [f64; 7]);
+
+/// A number with dimensions, like `nu [0 2 -1 0 0 0 0] 1e-05;`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DimensionedScalar<'a> {
+    /// The name of the quantity; the key of the entry if the entry has no name.
+    pub name: &'a str,
+
+    /// The dimensions of the value.
+    pub dimensions: Dimensions,
+
+    /// The value itself.
+    pub value: f64,
+}
+
+/// A vector with dimensions, like `g [0 1 -2 0 0 0 0] (0 -9.81 0);`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DimensionedVector<'a> {
+    /// The name of the quantity; the key of the entry if the entry has no name.
+    pub name: &'a str,
+
+    /// The dimensions of the value.
+    pub dimensions: Dimensions,
+
+    /// The value itself.
+    pub value: Vector,
+}
+
+/// A view over a dictionary of the tree that knows where it is: the keys leading to it from the
+/// root and the dictionaries around it. Lookups can go up to the enclosing dictionaries, like
+/// OpenFOAM does with `recursive` lookups.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor<'t, 'a> {
+    /// Dictionaries from the root to the current one.
+    scopes: Vec<&'t Dictionary<'a>>,
+
+    /// Keys from the root to the current dictionary.
+    path: Vec<&'a str>,
+}
+
+/// A single entry in a [`Dictionary`].
+#[derive(Debug, Clone)]
+pub struct Entry<'a> {
+    /// The key of the entry.
+    pub key: &'a str,
+
+    /// If the key was quoted in the content (e.g., `"inlet.*"`).
+    pub quoted: bool,
+
+    /// The values of the entry.
+    pub values: Vec<Foam<'a>>,
+
+    /// Where the entry was found in the content. Entries that were not parsed (e.g., added with
+    /// [`Dictionary::insert`]) have no location.
+    pub location: Option<Location>,
+
+    /// If the entry was copied from another dictionary by a merge (`$dict;`), the reference used
+    /// in the merge.
+    pub merged_from: Option<&'a str>,
+}
+
+/// Where, in the content, something was found.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Location {
+    /// Range of bytes in the content.
+    pub span: Range<usize>,
+
+    /// Line in the content, starting at 1.
+    pub line: usize,
+
+    /// Column in the line, starting at 1. Columns count characters, not bytes.
+    pub column: usize,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// An error, ready to be shown to people.
+///
+/// Diagnostics are created from a [`FoamError`] (with [`FoamError::diagnostic`]) and the content
+/// it came from. They own all their information, so they can be kept around after the content is
+/// gone, and are displayed in a format similar to the Rust compiler, pointing where, in the file,
+/// the problem is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// Name of the file (or anything that identifies where the content came from).
+    pub name: String,
+
+    /// Description of the error.
+    pub message: String,
+
+    /// Where the error happened, if known.
+    pub location: Option<Location>,
+
+    /// The line where the error happened, if known.
+    pub line: Option<String>,
+
+    /// A suggestion on how to fix the error.
+    pub hint: Option<&'static str>,
+
+    /// The diagnostic that caused this one, if any.
+    pub cause: Option<Box<Diagnostic>>,
+}
+
+/// Everything needed to expand a tree beyond its own content, like the files requested by
+/// `#include` directives. Used with [`Foam::expand_with`].
+///
+/// The context keeps the content of all the files it loaded, so the expanded tree can point to
+/// them; it must live as long as the expanded tree.
+#[derive(Default)]
+pub struct Context {
+    /// Where included files come from.
+    resolver: Option<Box<dyn Resolver>>,
+
+    /// Name of the file being expanded, if known.
+    name: Option<String>,
+
+    /// Values of the environment variables.
+    environment: Environment,
+
+    /// Content of the files loaded so far.
+    files: typed_arena::Arena<String>,
+}
+
+/// Environment variables, like `$FOAM_CASE` or `$HOME`, used when expanding the tree.
+///
+/// Variables can come from the process environment, from explicit values, or both (explicit
+/// values win). An empty environment (the default) has no variables, which makes expansion
+/// reproducible.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// If variables not set explicitly should be taken from the process environment.
+    process: bool,
+
+    /// Variables set explicitly.
+    variables: HashMap<String, String>,
+}
+
+/// The directives that include other files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncludeKind {
+    /// `#include`: the file must exist.
+    File,
+
+    /// `#includeIfPresent`: the file is ignored if it doesn't exist.
+    IfPresent,
+
+    /// `#includeEtc`: the file is searched in the OpenFOAM `etc` directories.
+    Etc,
+
+    /// `#includeFunc`: a function object configuration, searched in the case `system` directory
+    /// and then in the OpenFOAM `etc` directories.
+    Func,
+}
+
+/// A file found by a [`Resolver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Source {
+    /// Name of the file. It's used to find the files it includes, to detect files including each
+    /// other and in error messages.
+    pub name: String,
+
+    /// Content of the file.
+    pub content: String,
+}
+
+/// Finds the files requested by `#include` directives.
+///
+/// Besides [`FileSystem`], which reads files from disk, a `HashMap<String, String>` can be used as
+/// resolver, mapping the paths as they appear in the directives to their content.
+pub trait Resolver {
+    /// Find the file `path`, as it appears in the directive, requested by the file `parent` (if
+    /// known). Returns `None` if the file doesn't exist.
+    fn resolve(
+        &self,
+        kind: IncludeKind,
+        path: &str,
+        parent: Option<&str>,
+    ) -> std::io::Result<Option<Source>>;
+}
+
+/// A [`Resolver`] that reads files from disk.
+///
+/// Relative paths are relative to the directory of the file with the directive; for the root
+/// file, they are relative to the case directory (or the current directory, if no case directory
+/// is set).
+#[derive(Debug, Clone, Default)]
+pub struct FileSystem {
+    /// The case directory.
+    case: Option<PathBuf>,
+
+    /// The OpenFOAM `etc` directory.
+    etc: Option<PathBuf>,
 }
 
 /// Errors.
 #[derive(Debug, thiserror::Error, PartialEq)]
 pub enum FoamError<'a> {
-    #[error("Unexpected end of content")]
-    EndOfContent,
+    #[error("Unexpected end of content (at {location})")]
+    EndOfContent { location: Location },
+
+    #[error("Comment started but never closed (at {location})")]
+    UnterminatedComment { location: Location },
+
+    #[error("Verbatim block started but never closed (at {location})")]
+    UnterminatedVerbatim { location: Location },
 
-    #[error("While processing dictionary {name:?}, found not values")]
-    NoDictValues { name: &'a str },
+    #[error("Invalid character {text:?} (at {location})")]
+    InvalidCharacter { text: &'a str, location: Location },
 
-    #[error("Invalid end of a dictionary: {token:?}")]
-    InvalidDictEnd { token: &'a str },
+    #[error("While processing dictionary {name:?}, found not values (at {location})")]
+    NoDictValues { name: &'a str, location: Location },
 
-    #[error("Expected a keyword, found {token:?} (at {start} to {end})")]
-    MissingKeyword {
+    #[error("Invalid end of a dictionary: {token:?} (at {location})")]
+    InvalidDictEnd { token: &'a str, location: Location },
+
+    #[error("Expected a keyword, found {token:?} (at {location})")]
+    MissingKeyword { token: &'a str, location: Location },
+
+    #[error(
+        "Unexpected keyword {token:?} when processing {structure} (at {location})"
+    )]
+    UnexpectedToken {
         token: &'a str,
-        start: usize,
-        end: usize,
+        structure: &'a str,
+        location: Location,
+    },
+
+    #[error("Could not resolve {reference:?}{}", at(location))]
+    UnresolvedReference {
+        reference: &'a str,
+        location: Option<Location>,
+    },
+
+    #[error(
+        "{reference:?} is not a dictionary and can't be merged{}",
+        at(location)
+    )]
+    InvalidMerge {
+        reference: &'a str,
+        location: Option<Location>,
+    },
+
+    #[error("{reference:?} refers to itself{}", at(location))]
+    CyclicReference {
+        reference: &'a str,
+        location: Option<Location>,
+    },
+
+    #[error("Could not find {path:?} to include{}", at(location))]
+    IncludeNotFound {
+        path: &'a str,
+        location: Option<Location>,
+    },
+
+    #[error("Could not include {path:?}: {reason}{}", at(location))]
+    IncludeFailed {
+        path: &'a str,
+        reason: String,
+        location: Option<Location>,
+    },
+
+    #[error("Files include each other: {}{}", chain.join(" -> "), at(location))]
+    IncludeCycle {
+        chain: Vec<String>,
+        location: Option<Location>,
     },
 
-    #[error("Unexpected keyword {token:?} when processing {structure}")]
-    UnexpectedToken { token: &'a str, structure: &'a str },
+    #[error("Conditional block started but never closed (at {location})")]
+    UnterminatedConditional { location: Location },
+
+    #[error("Invalid arguments for {directive}{}", at(location))]
+    InvalidDirective {
+        directive: &'a str,
+        location: Option<Location>,
+    },
+
+    #[error(
+        "Invalid regular expression {pattern:?}: {reason}{}",
+        at(location)
+    )]
+    InvalidPattern {
+        pattern: &'a str,
+        reason: String,
+        location: Option<Location>,
+    },
+
+    #[error("Invalid expression {expression:?}: {reason}{}", at(location))]
+    InvalidExpression {
+        expression: &'a str,
+        reason: String,
+        location: Option<Location>,
+    },
+
+    #[error("In {file:?}: {error}")]
+    InInclude {
+        file: String,
+        location: Option<Location>,
+        error: Box<FoamError<'a>>,
+    },
+
+    #[error("Invalid path {path:?}, at {segment:?}: {reason}")]
+    InvalidPath {
+        path: String,
+        segment: String,
+        reason: &'static str,
+    },
+
+    #[error("{key:?} should be a number, but is {text:?}")]
+    InvalidNumber { key: String, text: &'a str },
+
+    #[error("{key:?} should be an integer, but is {text:?}")]
+    InvalidLabel { key: String, text: &'a str },
+
+    #[error("{key:?} should be a switch, but is {text:?}")]
+    InvalidSwitch { key: String, text: &'a str },
+
+    #[error("{key:?} should be a word, but is {text:?}")]
+    InvalidWord { key: String, text: &'a str },
+
+    #[error("{key:?} has no dimensions{}", at(location))]
+    MissingDimensions {
+        key: &'a str,
+        location: Option<Location>,
+    },
+
+    #[error("Invalid dimensions for {key:?}: {reason}{}", at(location))]
+    InvalidDimensions {
+        key: &'a str,
+        reason: String,
+        location: Option<Location>,
+    },
+
+    #[error("Invalid value for {key:?}: {reason}{}", at(location))]
+    InvalidDimensionedValue {
+        key: &'a str,
+        reason: &'static str,
+        location: Option<Location>,
+    },
 
     #[error(
         "Requested key from dictionary, but current object is not a dictionary"
@@ -72,6 +516,19 @@ pub enum FoamError<'a> {
     #[error("The current element is not a value")]
     NotAValue,
 
+    #[error("The current element is not a list")]
+    NotAList,
+
+    #[error("A {kind} has {expected} components, but the list has {found}")]
+    ComponentCount {
+        kind: &'static str,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("{text:?} is not a valid {kind} component")]
+    InvalidComponent { kind: &'static str, text: &'a str },
+
     #[error("The requested key does not exist")]
     NoSuchKey,
 
@@ -80,3 +537,11 @@ pub enum FoamError<'a> {
     )]
     NoSuchValue,
 }
+
+/// Format an optional location for error messages.
+fn at(location: &Option<Location>) -> String {
+    location
+        .as_ref()
+        .map(|location| format!(" (at {})", location))
+        .unwrap_or_default()
+}
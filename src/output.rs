@@ -2,12 +2,11 @@
 
 use std::fmt::Display;
 
+use crate::parser::SINGLE_ARGUMENT_DIRECTIVES;
+use crate::Conditional;
+use crate::Dictionary;
 use crate::Foam;
 
-/// List of characters that will force values to be quoted.
-/// (We could easily add quotes everywhere, but better like this).
-const NEED_QUOTE: &str = " \t()[]{}*\"";
-
 impl<'a> Foam<'a> {
     fn display(
         &self,
@@ -15,59 +14,115 @@ impl<'a> Foam<'a> {
         f: &mut std::fmt::Formatter<'_>,
     ) -> std::fmt::Result {
         let in_level = " ".repeat(level * 3);
-        write!(f, "{}", in_level)?;
 
         match self {
-            Foam::Dictionary(map) => {
-                for (key, content) in map {
-                    write!(f, "{}", safe_keyword(key))?;
-
-                    if level != 0 {
-                        // Root is a FoamDictionary, but we don't wrap things in a dictionary
-                        write!(f, "\n{}{{", in_level)?;
-                    }
-
-                    let mut need_quote = true;
-                    for element in content {
-                        if let Foam::List(_) = element {
-                            need_quote = false;
-                        }
-                        element.display(level + 1, f)?;
-                    }
-                    if need_quote {
-                        writeln!(f, ";")?;
-                    }
-                }
-                write!(f, "")
+            // Root is a FoamDictionary, but we don't wrap things in a dictionary
+            Foam::Dictionary(dict) if level == 0 => display_entries(dict, 0, f),
+            Foam::Dictionary(dict) => {
+                writeln!(f, "{}{{", in_level)?;
+                display_entries(dict, level + 1, f)?;
+                writeln!(f, "{}}}", in_level)
             }
-            Foam::Value(value) => write!(f, "{} ", safe_keyword(value)),
+            Foam::Value(value) => {
+                write!(f, "{}{} ", in_level, value)
+            }
+            Foam::Calculated(value) => write!(f, "{}{} ", in_level, value),
             Foam::List(values) => {
-                writeln!(f, "(")?;
+                writeln!(f, "{}(", in_level)?;
                 for element in values {
                     element.display(level + 1, f)?;
+                    // Inner lists go in their own lines (dictionaries already end with a new line).
+                    if let Foam::List(_) = element {
+                        writeln!(f)?;
+                    }
                 }
-                writeln!(f)?;
-                writeln!(f, "{});", in_level)
+                if !matches!(
+                    values.last(),
+                    Some(Foam::List(_)) | Some(Foam::Dictionary(_))
+                ) {
+                    writeln!(f)?;
+                }
+                write!(f, "{})", in_level)
             }
+            Foam::String(value) => write!(f, "{}\"{}\" ", in_level, value),
+            Foam::Verbatim(value) => write!(f, "{}#{{{}#}} ", in_level, value),
             Foam::Dimension(values) => {
-                write!(f, "[ ")?;
-                for element in values {
-                    write!(f, "{} ", element)?;
-                }
-                write!(f, "];")
+                write!(f, "{}[{}] ", in_level, values.join(" "))
+            }
+            Foam::CodeStream(code) => {
+                writeln!(f, "{}#codeStream", in_level)?;
+                writeln!(f, "{}{{", in_level)?;
+                display_entries(code, level + 1, f)?;
+                write!(f, "{}}} ", in_level)
+            }
+            Foam::Conditional(conditional) => {
+                display_conditional(conditional, level, f)
+            }
+        }
+    }
+}
+
+/// Display the entries of a dictionary, in order.
+fn display_entries(
+    dict: &Dictionary,
+    level: usize,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    let in_level = " ".repeat(level * 3);
+    for entry in dict.entries() {
+        if let [Foam::Conditional(conditional)] = entry.values.as_slice() {
+            display_conditional(conditional, level, f)?;
+            continue;
+        }
+
+        if entry.quoted {
+            write!(f, "{}\"{}\"", in_level, entry.key)?;
+        } else {
+            write!(f, "{}{}", in_level, entry.key)?;
+        }
+
+        // Dictionaries are closed by their braces; everything else needs a `;`.
+        let mut need_end = true;
+        for element in &entry.values {
+            if let Foam::Dictionary(inner) = element {
+                writeln!(f)?;
+                writeln!(f, "{}{{", in_level)?;
+                display_entries(inner, level + 1, f)?;
+                writeln!(f, "{}}}", in_level)?;
+                need_end = false;
+            } else {
+                element.display(level + 1, f)?;
+                need_end = true;
             }
         }
+        if SINGLE_ARGUMENT_DIRECTIVES.contains(&entry.key) {
+            writeln!(f)?;
+        } else if need_end {
+            writeln!(f, ";")?;
+        }
     }
+    Ok(())
 }
 
-/// Checks a keyword for any characters that may force it to be quoted. Returns the quoted string
-/// if needed, or the same string back if it doesn't.
-fn safe_keyword(keyword: &str) -> String {
-    if keyword.chars().any(|char| NEED_QUOTE.contains(char)) {
-        format!("\"{}\"", keyword)
-    } else {
-        keyword.to_string()
+/// Display a conditional block. The directives are in their own lines, with the condition in
+/// the same line as the directive.
+fn display_conditional(
+    conditional: &Conditional,
+    level: usize,
+    f: &mut std::fmt::Formatter<'_>,
+) -> std::fmt::Result {
+    let in_level = " ".repeat(level * 3);
+    write!(f, "{}{}", in_level, conditional.directive)?;
+    for value in &conditional.condition {
+        write!(f, " {}", value.to_string().trim_end())?;
     }
+    writeln!(f)?;
+    display_entries(&conditional.then, level, f)?;
+    if let Some(otherwise) = &conditional.otherwise {
+        writeln!(f, "{}#else", in_level)?;
+        display_entries(otherwise, level, f)?;
+    }
+    writeln!(f, "{}#endif", in_level)
 }
 
 impl<'a> Display for Foam<'a> {
@@ -87,15 +142,47 @@ mod test {
         assert_eq!(formatted, "var   value ;\n");
     }
 
-    // Issue: HashMaps in Rust have absolutely no guarantee of order, even when run more than once.
-    //        (or maybe I just need better key names)
-    // #[test]
-    // fn two_values() {
-    //     let data = Foam::parse("var1 value1; var2 value2;").unwrap();
-    //     let formatted = data.to_string();
-    //     // Our dictionaries do not guarantee order (this is not in the spec, anyway)
-    //     assert_eq!(formatted, "var2   value2 ;\nvar1   value1 ;\n");
-    // }
+    #[test]
+    fn two_values() {
+        let data = Foam::parse("var2 value2; var1 value1;").unwrap();
+        let formatted = data.to_string();
+        assert_eq!(formatted, "var2   value2 ;\nvar1   value1 ;\n");
+    }
+
+    #[test]
+    fn duplicates() {
+        let data = Foam::parse("a 1; b 2; a 3;").unwrap();
+        let formatted = data.to_string();
+        assert_eq!(formatted, "a   1 ;\nb   2 ;\na   3 ;\n");
+    }
+
+    #[test]
+    fn nested_dicts() {
+        let data = Foam::parse("b { z 1; y { x 2; } } a 3;").unwrap();
+        let formatted = data.to_string();
+        assert_eq!(
+            formatted,
+            "b\n{\n   z      1 ;\n   y\n   {\n      x         2 ;\n   }\n}\na   3 ;\n"
+        );
+    }
+
+    #[test]
+    fn dicts_in_lists() {
+        let data = Foam::parse("patches ( { name a; } (1 2) );").unwrap();
+        let formatted = data.to_string();
+        assert_eq!(
+            formatted,
+            "patches   (\n      {\n         name            a ;\n      }\n      (\n         1          2 \n      )\n   );\n"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let data =
+            Foam::parse(include_str!("../resources/createPatchDict")).unwrap();
+        let formatted = data.to_string();
+        assert_eq!(Foam::parse(&formatted), Ok(data));
+    }
 
     #[test]
     fn a_list() {
@@ -103,4 +190,72 @@ mod test {
         let formatted = data.to_string();
         assert_eq!(formatted, "var   (\n      1       2       3 \n   );\n");
     }
+
+    #[test]
+    fn function_keyword() {
+        let data = Foam::parse("div(phi,U) Gauss linear;").unwrap();
+        let formatted = data.to_string();
+        assert_eq!(formatted, "div(phi,U)   Gauss    linear ;\n");
+    }
+
+    #[test]
+    fn strings() {
+        let data =
+            Foam::parse(r#""inlet.*" { name "a \"b\"" word ""; }"#).unwrap();
+        let formatted = data.to_string();
+        assert_eq!(
+            formatted,
+            "\"inlet.*\"\n{\n   name      \"a \\\"b\\\"\"       word       \"\" ;\n}\n"
+        );
+        assert_eq!(Foam::parse(&formatted), Ok(data));
+    }
+
+    #[test]
+    fn verbatim() {
+        let content = "code\n#{\n    int a = 1;\n    { a++; }\n#};\n";
+        let data = Foam::parse(content).unwrap();
+        let formatted = data.to_string();
+        assert_eq!(
+            formatted,
+            "code   #{\n    int a = 1;\n    { a++; }\n#} ;\n"
+        );
+        assert_eq!(Foam::parse(&formatted), Ok(data));
+    }
+
+    #[test]
+    fn dimension() {
+        let data = Foam::parse("nu [0 2 -1 0 0 0 0] 1;").unwrap();
+        let formatted = data.to_string();
+        assert_eq!(formatted, "nu   [0 2 -1 0 0 0 0]    1 ;\n");
+    }
+
+    #[test]
+    fn conditional() {
+        let data = Foam::parse(
+            "d {\n#ifeq $a b\nx 1;\n#else\ny 2;\n#endif\n}\n#if #calc \"1 < 2\"\nz 3;\n#endif",
+        )
+        .unwrap();
+        let result = format!("{}", data);
+        assert_eq!(
+            result,
+            "d\n{\n   #ifeq $a b\n   x      1 ;\n   #else\n   y      2 ;\n   #endif\n}\n\
+             #if #calc \"1 < 2\"\nz   3 ;\n#endif\n"
+        );
+        assert_eq!(Foam::parse(&result), Ok(data));
+    }
+
+    #[test]
+    fn code_stream() {
+        let data = Foam::parse(
+            "inertia #codeStream\n{\n    codeOptions \"-I$(LIB_SRC)\";\n    code #{\n  os << 1;\n#};\n};",
+        )
+        .unwrap();
+        let result = format!("{}", data);
+        assert_eq!(
+            result,
+            "inertia   #codeStream\n   {\n      codeOptions         \"-I$(LIB_SRC)\" ;\n      \
+             code         #{\n  os << 1;\n#} ;\n   } ;\n"
+        );
+        assert_eq!(Foam::parse(&result), Ok(data));
+    }
 }
@@ -1,138 +1,645 @@
 //! Generates the Foam structure from a source.
 
-use std::collections::HashMap;
-
 use logos::Logos;
 
+use crate::tokenizer::LexerError;
 use crate::tokenizer::Token;
+use crate::Conditional;
+use crate::Dictionary;
+use crate::Entry;
 use crate::Foam;
 use crate::FoamError;
+use crate::Location;
 
 impl<'a> Foam<'a> {
     pub fn parse(content: &'a str) -> Result<Foam<'a>, FoamError<'a>> {
         let mut lexer = Token::lexer(content);
-        get_dict(&mut lexer)
+        get_dict(&mut lexer, &mut None, false)
+    }
+
+    /// Parse the content, but instead of stopping at the first error, skip to the end of the
+    /// current entry (`;`) or dictionary (`}`) and continue from there. Returns whatever could be
+    /// parsed and all the errors found.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// let (root, errors) = Foam::parse_recovering("a 1; b ( 2 }; c @; d 4;");
+    /// assert_eq!(errors.len(), 2);
+    /// assert_eq!(root.get_first_value("a"), Ok("1"));
+    /// assert_eq!(root.get_first_value("d"), Ok("4"));
+    /// assert!(root.get("b").is_err());
+    /// ```
+    pub fn parse_recovering(
+        content: &'a str,
+    ) -> (Foam<'a>, Vec<FoamError<'a>>) {
+        let mut lexer = Token::lexer(content);
+        let mut errors = Some(Vec::new());
+        let root = get_dict(&mut lexer, &mut errors, false);
+        let mut errors = errors.unwrap_or_default();
+        match root {
+            Ok(root) => (root, errors),
+            Err(error) => {
+                errors.push(error);
+                (Foam::Dictionary(Dictionary::new()), errors)
+            }
+        }
     }
 }
 
+/// Directives that take a single argument and, unlike entries, are not closed by a `;`.
+pub(crate) const SINGLE_ARGUMENT_DIRECTIVES: &[&str] = &[
+    "#include",
+    "#includeIfPresent",
+    "#includeEtc",
+    "#includeFunc",
+    "#remove",
+];
+
+/// Location of the current token.
+fn location<'a>(lexer: &mut logos::Lexer<'a, Token<'a>>) -> Location {
+    let content = lexer.source();
+    let span = lexer.span();
+    lexer.extras.locate(content, span)
+}
+
+/// Location of the end of the content.
+fn end_of_content<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+) -> FoamError<'a> {
+    let content = lexer.source();
+    let end = content.len();
+    FoamError::EndOfContent {
+        location: lexer.extras.locate(content, end..end),
+    }
+}
+
+/// Convert an error from the tokenizer to our own errors.
+fn lexer_error<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    error: LexerError,
+) -> FoamError<'a> {
+    match error {
+        LexerError::InvalidToken => FoamError::InvalidCharacter {
+            text: lexer.slice(),
+            location: location(lexer),
+        },
+        LexerError::UnterminatedComment => FoamError::UnterminatedComment {
+            location: location(lexer),
+        },
+        LexerError::UnterminatedVerbatim => FoamError::UnterminatedVerbatim {
+            location: location(lexer),
+        },
+    }
+}
+
+/// Process a dictionary. Nested dictionaries end with a `}`; the root goes till the end of the
+/// content.
 fn get_dict<'a>(
     lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+    nested: bool,
 ) -> Result<Foam<'a>, FoamError<'a>> {
-    let mut result = HashMap::new();
+    let (result, _) = get_entries(lexer, errors, nested, false)?;
+    Ok(Foam::Dictionary(result))
+}
+
+/// Read the entries of a dictionary, till its end. Inside a conditional block (`in_conditional`),
+/// the entries go till the `#else` or `#endif` that ends the branch, which is returned.
+///
+/// Only `nested` dictionaries can be closed with `}`; at the root, it is an error.
+fn get_entries<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+    nested: bool,
+    in_conditional: bool,
+) -> Result<(Dictionary<'a>, Option<&'a str>), FoamError<'a>> {
+    let mut result = Dictionary::new();
     let mut key = None;
     let mut key_values = Vec::new();
+    let mut branch_end = None;
     loop {
         let token = lexer.next();
         tracing::debug!(?token);
-        match token {
-            None => break,
-            Some(Err(_)) => return Err(FoamError::EndOfContent),
+        if let Some(Ok(Token::Directive(directive @ ("#else" | "#endif")))) =
+            token
+        {
+            if in_conditional && key.is_none() {
+                branch_end = Some(directive);
+                break;
+            }
+        }
 
-            Some(Ok(Token::ListEnd)) => {
-                return Err(FoamError::UnexpectedToken {
-                    token: ")",
-                    structure: "dictionary",
-                })
+        if !nested && matches!(token, Some(Ok(Token::DictEnd))) {
+            let error = FoamError::UnexpectedToken {
+                token: "}",
+                structure: "dictionary",
+                location: location(lexer),
+            };
+            match errors {
+                // Nothing was opened, so there is nothing to skip.
+                Some(errors) => {
+                    errors.push(error);
+                    continue;
+                }
+                None => return Err(error),
             }
-            // Some elements are only possible if we have a defined key. For example, doing `{(1)}`
-            // is not valid, 'cause we don't have the dictionary key yet.
-            Some(Ok(Token::ListStart)) if key.is_none() => {
-                return Err(FoamError::UnexpectedToken {
-                    token: "(",
+        }
+
+        match dict_token(
+            lexer,
+            token,
+            &mut result,
+            &mut key,
+            &mut key_values,
+            errors,
+            nested,
+        ) {
+            Ok(true) => continue,
+            Ok(false) => break,
+            Err(error) => {
+                // Whatever we had for the current entry is lost.
+                key = None;
+                key_values.clear();
+                if !recover(lexer, errors, error, nested)? {
+                    break;
+                }
+            }
+        }
+    }
+    if let Some(mut entry) = key {
+        tracing::debug!(?entry, ?key_values);
+        entry.values = key_values;
+        add_entry(&mut result, entry, errors);
+    }
+
+    Ok((result, branch_end))
+}
+
+/// Add an entry to the dictionary. A quoted key that isn't a valid pattern is kept as a literal key,
+/// so parsing doesn't fail; when recovering from errors, it is reported too.
+fn add_entry<'a>(
+    result: &mut Dictionary<'a>,
+    entry: Entry<'a>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+) {
+    if let (Some(error), Some(errors)) = (result.push(entry), errors) {
+        errors.push(error);
+    }
+}
+
+/// Process a single token inside a dictionary. Returns `false` when the dictionary is over.
+fn dict_token<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    token: Option<Result<Token<'a>, LexerError>>,
+    result: &mut Dictionary<'a>,
+    key: &mut Option<Entry<'a>>,
+    key_values: &mut Vec<Foam<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+    nested: bool,
+) -> Result<bool, FoamError<'a>> {
+    match token {
+        None => return Ok(false),
+        Some(Err(error)) => return Err(lexer_error(lexer, error)),
+
+        Some(Ok(Token::ListEnd)) => {
+            return Err(FoamError::UnexpectedToken {
+                token: ")",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        // Some elements are only possible if we have a defined key. For example, doing `{(1)}`
+        // is not valid, 'cause we don't have the dictionary key yet.
+        Some(Ok(Token::ListStart)) if key.is_none() => {
+            return Err(FoamError::UnexpectedToken {
+                token: "(",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        // Same as above
+        Some(Ok(Token::DictStart)) if key.is_none() => {
+            return Err(FoamError::UnexpectedToken {
+                token: "{",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        // Same as above
+        Some(Ok(Token::DimensionStart)) if key.is_none() => {
+            return Err(FoamError::UnexpectedToken {
+                token: "[",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        Some(Ok(Token::DimensionEnd)) => {
+            return Err(FoamError::UnexpectedToken {
+                token: "]",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        // `;` is acceptable only if we are alredy processing a list of values, like
+        // `dict { list 1 2 3; }`. If we see the `;` and we are not processing a list, then
+        // something is wrong.
+        Some(Ok(Token::End)) if key.is_none() => {
+            return Err(FoamError::UnexpectedToken {
+                token: ";",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+
+        // As per the rule above, if we have the key, then we have a list of elements, and we
+        // can just push them into the current key. This resets the key and its values.
+        Some(Ok(Token::End)) => {
+            tracing::debug!(?key, ?key_values);
+            let mut entry = key.take().unwrap();
+            entry.values = std::mem::take(key_values);
+            add_entry(result, entry, errors);
+        }
+
+        Some(Ok(Token::MultilineComment(_))) => {}
+        Some(Ok(Token::Comment(_))) => {}
+
+        Some(Ok(Token::Keyword(token))) if key.is_none() => {
+            *key = Some(Entry::new(token, false, location(lexer)));
+        }
+        Some(Ok(Token::Directive(token)))
+            if key.is_none() && SINGLE_ARGUMENT_DIRECTIVES.contains(&token) =>
+        {
+            let mut entry = Entry::new(token, false, location(lexer));
+            entry.values = vec![directive_argument(lexer, errors)?];
+            add_entry(result, entry, errors);
+        }
+        Some(Ok(Token::Directive(token @ ("#if" | "#ifeq"))))
+            if key.is_none() =>
+        {
+            let mut entry = Entry::new(token, false, location(lexer));
+            entry.values = vec![get_conditional(lexer, errors, token, nested)?];
+            add_entry(result, entry, errors);
+        }
+        // Branches end where they are expected (see `get_entries`), so these are out of place.
+        Some(Ok(Token::Directive(token @ ("#else" | "#endif"))))
+            if key.is_none() =>
+        {
+            return Err(FoamError::UnexpectedToken {
+                token,
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        Some(Ok(Token::Directive(token))) if key.is_none() => {
+            *key = Some(Entry::new(token, false, location(lexer)));
+        }
+        Some(Ok(Token::Directive("#codeStream"))) => {
+            key_values.push(get_code_stream(lexer, errors)?);
+            tracing::debug!(?key, ?key_values);
+        }
+        Some(Ok(Token::Directive(token))) => {
+            key_values.push(Foam::Value(token));
+            tracing::debug!(?key, ?key_values);
+        }
+        Some(Ok(Token::String(token))) if key.is_none() => {
+            *key = Some(Entry::new(token, true, location(lexer)));
+        }
+        Some(Ok(Token::Keyword(token))) => {
+            key_values.push(Foam::Value(token));
+            tracing::debug!(?key, ?key_values);
+        }
+        Some(Ok(Token::String(token))) => {
+            key_values.push(Foam::String(token));
+            tracing::debug!(?key, ?key_values);
+        }
+        Some(Ok(Token::Verbatim(_))) if key.is_none() => {
+            return Err(FoamError::UnexpectedToken {
+                token: "#{",
+                structure: "dictionary",
+                location: location(lexer),
+            })
+        }
+        Some(Ok(Token::Verbatim(token))) => {
+            key_values.push(Foam::Verbatim(token));
+            tracing::debug!(?key, ?key_values);
+        }
+        Some(Ok(Token::ListStart)) => key_values.push(get_list(lexer, errors)?),
+        Some(Ok(Token::DimensionStart)) => {
+            key_values.push(get_dimension(lexer)?)
+        }
+        Some(Ok(Token::DictStart)) => {
+            let inner = get_dict(lexer, errors, true)?;
+            let mut entry = key.take().unwrap();
+            entry.values = vec![inner];
+            add_entry(result, entry, errors);
+            key_values.clear();
+        }
+
+        Some(Ok(Token::DictEnd)) => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// Deal with an error inside a dictionary.
+///
+/// If we are not recovering from errors, this just gives the error back. Otherwise, the error is
+/// stored and tokens are skipped till the end of the current entry (`;`) or the end of the current
+/// dictionary (`}`), so we can continue from there. Returns `false` if the dictionary is over.
+/// The root dictionary is not closed by `}`, so a stray one there is also stored as an error.
+fn recover<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+    error: FoamError<'a>,
+    nested: bool,
+) -> Result<bool, FoamError<'a>> {
+    let Some(errors) = errors else {
+        return Err(error);
+    };
+    let at_end_of_content = matches!(error, FoamError::EndOfContent { .. });
+    let at_end_of_entry = lexer.slice() == ";";
+    errors.push(error);
+    if at_end_of_content {
+        return Ok(false);
+    }
+    if at_end_of_entry {
+        return Ok(true);
+    }
+
+    // Anything opened after the error must be closed before we can consider the entry done.
+    let mut depth = 0usize;
+    loop {
+        match lexer.next() {
+            None => return Ok(false),
+            Some(Ok(Token::End)) if depth == 0 => return Ok(true),
+            Some(Ok(Token::DictEnd)) if depth == 0 && nested => {
+                return Ok(false)
+            }
+            Some(Ok(Token::DictEnd)) if depth == 0 => {
+                errors.push(FoamError::UnexpectedToken {
+                    token: "}",
                     structure: "dictionary",
+                    location: location(lexer),
                 })
             }
-            // Same as above
-            Some(Ok(Token::DictStart)) if key.is_none() => {
+            Some(Ok(
+                Token::DictStart | Token::ListStart | Token::DimensionStart,
+            )) => depth += 1,
+            Some(Ok(Token::DictEnd | Token::ListEnd | Token::DimensionEnd)) => {
+                depth = depth.saturating_sub(1)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn get_list<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+) -> Result<Foam<'a>, FoamError<'a>> {
+    let mut result = Vec::new();
+    loop {
+        let token = lexer.next();
+        tracing::debug!(?token);
+        match token {
+            None => return Err(end_of_content(lexer)),
+            Some(Err(error)) => return Err(lexer_error(lexer, error)),
+
+            Some(Ok(Token::DictEnd)) => {
                 return Err(FoamError::UnexpectedToken {
-                    token: "{",
-                    structure: "dictionary",
+                    token: "}",
+                    structure: "list",
+                    location: location(lexer),
                 })
             }
-            // `;` is acceptable only if we are alredy processing a list of values, like
-            // `dict { list 1 2 3; }`. If we see the `;` and we are not processing a list, then
-            // something is wrong.
-            Some(Ok(Token::End)) if key.is_none() => {
+            Some(Ok(Token::End)) => {
                 return Err(FoamError::UnexpectedToken {
                     token: ";",
-                    structure: "dictionary",
+                    structure: "list",
+                    location: location(lexer),
                 })
             }
-
-            // As per the rule above, if we have the key, then we have a list of elements, and we
-            // can just push them into the current key. This resets the key and its values.
-            Some(Ok(Token::End)) => {
-                tracing::debug!(?key, ?key_values);
-                result.insert(key.unwrap(), key_values);
-                key = None;
-                key_values = Vec::new();
+            Some(Ok(Token::DimensionEnd)) => {
+                return Err(FoamError::UnexpectedToken {
+                    token: "]",
+                    structure: "list",
+                    location: location(lexer),
+                })
             }
 
             Some(Ok(Token::MultilineComment(_))) => continue,
             Some(Ok(Token::Comment(_))) => continue,
 
-            Some(Ok(Token::Keyword(token))) if key.is_none() => {
-                key = Some(token);
+            Some(Ok(Token::Keyword(token) | Token::Directive(token))) => {
+                result.push(Foam::Value(token))
             }
-            Some(Ok(Token::Keyword(token))) => {
-                key_values.push(Foam::Value(token));
-                tracing::debug!(?key, ?key_values);
+            Some(Ok(Token::String(token))) => result.push(Foam::String(token)),
+            Some(Ok(Token::Verbatim(token))) => {
+                result.push(Foam::Verbatim(token))
             }
-            Some(Ok(Token::ListStart)) => key_values.push(get_list(lexer)?),
             Some(Ok(Token::DictStart)) => {
-                result.insert(key.unwrap(), vec![get_dict(lexer)?]);
-                key = None;
-                key_values = Vec::new();
+                result.push(get_dict(lexer, errors, true)?)
             }
-
-            Some(Ok(Token::DictEnd)) => {
-                break;
+            Some(Ok(Token::ListStart)) => result.push(get_list(lexer, errors)?),
+            Some(Ok(Token::DimensionStart)) => {
+                result.push(get_dimension(lexer)?)
             }
+
+            Some(Ok(Token::ListEnd)) => break,
         }
     }
-    if let Some(key) = key {
-        tracing::debug!(?key, ?key_values);
-        result.insert(key, key_values);
-    }
+    Ok(Foam::List(result))
+}
 
-    Ok(Foam::Dictionary(result))
+/// Read the argument of a directive, like the file name in `#include "file"`. Directives don't
+/// need a `;`, but one right after the argument is accepted (and ignored).
+fn directive_argument<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+) -> Result<Foam<'a>, FoamError<'a>> {
+    let argument = loop {
+        let token = lexer.next();
+        tracing::debug!(?token);
+        match token {
+            None => return Err(end_of_content(lexer)),
+            Some(Err(error)) => return Err(lexer_error(lexer, error)),
+
+            Some(Ok(Token::MultilineComment(_))) => continue,
+            Some(Ok(Token::Comment(_))) => continue,
+
+            Some(Ok(Token::Keyword(token))) => break Foam::Value(token),
+            Some(Ok(Token::String(token))) => break Foam::String(token),
+            Some(Ok(Token::ListStart)) => break get_list(lexer, errors)?,
+
+            Some(Ok(_)) => {
+                return Err(FoamError::UnexpectedToken {
+                    token: lexer.slice(),
+                    structure: "directive",
+                    location: location(lexer),
+                })
+            }
+        }
+    };
+
+    let remainder = lexer.remainder();
+    let trimmed = remainder.trim_start();
+    if trimmed.starts_with(';') {
+        lexer.bump(remainder.len() - trimmed.len() + 1);
+    }
+    Ok(argument)
 }
 
-fn get_list<'a>(
+/// Process a `#codeStream` directive, after the directive itself. The directive is followed by a
+/// dictionary with the code.
+fn get_code_stream<'a>(
     lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
 ) -> Result<Foam<'a>, FoamError<'a>> {
-    let mut result = Vec::new();
     loop {
         let token = lexer.next();
         tracing::debug!(?token);
         match token {
-            None => return Err(FoamError::EndOfContent),
-            Some(Err(_)) => return Err(FoamError::EndOfContent),
+            None => return Err(end_of_content(lexer)),
+            Some(Err(error)) => return Err(lexer_error(lexer, error)),
 
-            Some(Ok(Token::DictEnd)) => {
+            Some(Ok(Token::MultilineComment(_))) => continue,
+            Some(Ok(Token::Comment(_))) => continue,
+
+            Some(Ok(Token::DictStart)) => break,
+            Some(Ok(_)) => {
                 return Err(FoamError::UnexpectedToken {
-                    token: "}",
-                    structure: "list",
+                    token: lexer.slice(),
+                    structure: "#codeStream",
+                    location: location(lexer),
                 })
             }
-            Some(Ok(Token::End)) => {
+        }
+    }
+
+    let (code, _) = get_entries(lexer, errors, true, false)?;
+    Ok(Foam::CodeStream(code))
+}
+
+/// Process a conditional block, after its directive (`#if` or `#ifeq`).
+fn get_conditional<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+    errors: &mut Option<Vec<FoamError<'a>>>,
+    directive: &'a str,
+    nested: bool,
+) -> Result<Foam<'a>, FoamError<'a>> {
+    let start = location(lexer);
+    let condition = get_condition(lexer)?;
+    let (then, branch_end) = get_entries(lexer, errors, nested, true)?;
+    let otherwise = match branch_end {
+        Some("#endif") => None,
+        Some(_) => match get_entries(lexer, errors, nested, true)? {
+            (otherwise, Some("#endif")) => Some(otherwise),
+            (_, Some(token)) => {
                 return Err(FoamError::UnexpectedToken {
-                    token: ";",
-                    structure: "list",
+                    token,
+                    structure: "conditional",
+                    location: location(lexer),
+                })
+            }
+            (_, None) => {
+                return Err(FoamError::UnterminatedConditional {
+                    location: start,
                 })
             }
+        },
+        None => {
+            return Err(FoamError::UnterminatedConditional { location: start })
+        }
+    };
+
+    Ok(Foam::Conditional(Conditional {
+        directive,
+        condition,
+        then,
+        otherwise,
+    }))
+}
+
+/// Read the condition of a conditional block, which goes till the end of the line.
+fn get_condition<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+) -> Result<Vec<Foam<'a>>, FoamError<'a>> {
+    let line_end = lexer
+        .remainder()
+        .find('\n')
+        .map_or(lexer.source().len(), |pos| lexer.span().end + pos);
+    let mut condition = Vec::new();
+    loop {
+        // Tokens can only be consumed if they are still in the same line.
+        let mut ahead = lexer.clone();
+        let token = ahead.next();
+        if token.is_none() || ahead.span().start >= line_end {
+            break;
+        }
+        *lexer = ahead;
+
+        tracing::debug!(?token);
+        match token {
+            None => break,
+            Some(Err(error)) => return Err(lexer_error(lexer, error)),
 
             Some(Ok(Token::MultilineComment(_))) => continue,
             Some(Ok(Token::Comment(_))) => continue,
 
-            Some(Ok(Token::Keyword(token))) => result.push(Foam::Value(token)),
-            Some(Ok(Token::DictStart)) => result.push(get_dict(lexer)?),
-            Some(Ok(Token::ListStart)) => result.push(get_list(lexer)?),
+            Some(Ok(Token::Keyword(token) | Token::Directive(token))) => {
+                condition.push(Foam::Value(token))
+            }
+            Some(Ok(Token::String(token))) => {
+                condition.push(Foam::String(token))
+            }
+            Some(Ok(Token::Verbatim(token))) => {
+                condition.push(Foam::Verbatim(token))
+            }
 
-            Some(Ok(Token::ListEnd)) => break,
+            Some(Ok(_)) => {
+                return Err(FoamError::UnexpectedToken {
+                    token: lexer.slice(),
+                    structure: "condition",
+                    location: location(lexer),
+                })
+            }
         }
     }
-    Ok(Foam::List(result))
+    Ok(condition)
+}
+
+/// Process a dimension set, like `[0 2 -1 0 0 0 0]`.
+///
+/// Besides the full 7 entries form, OpenFOAM also accepts the short form with 5 entries (no
+/// current or luminous intensity) and the named units form, like `[m^2 s^-1]`. We don't check the
+/// entries here, they are just captured in the order they appear.
+fn get_dimension<'a>(
+    lexer: &mut logos::Lexer<'a, Token<'a>>,
+) -> Result<Foam<'a>, FoamError<'a>> {
+    let mut result = Vec::new();
+    loop {
+        let token = lexer.next();
+        tracing::debug!(?token);
+        match token {
+            None => return Err(end_of_content(lexer)),
+            Some(Err(error)) => return Err(lexer_error(lexer, error)),
+
+            Some(Ok(Token::MultilineComment(_))) => continue,
+            Some(Ok(Token::Comment(_))) => continue,
+
+            Some(Ok(Token::Keyword(token))) => result.push(token),
+            Some(Ok(Token::DimensionEnd)) => break,
+
+            // Dimensions are flat; there is nothing else that could be inside them.
+            Some(Ok(_)) => {
+                return Err(FoamError::UnexpectedToken {
+                    token: lexer.slice(),
+                    structure: "dimension",
+                    location: location(lexer),
+                })
+            }
+        }
+    }
+    Ok(Foam::Dimension(result))
 }
 
 #[cfg(test)]
@@ -142,14 +649,14 @@ mod test {
     #[test]
     fn single_attribution() {
         let result = Foam::parse("variable value;");
-        let map = HashMap::from([("variable", vec![Foam::Value("value")])]);
+        let map = Dictionary::from([("variable", vec![Foam::Value("value")])]);
         assert_eq!(result, Ok(Foam::Dictionary(map)));
     }
 
     #[test]
     fn mutiple_attributions() {
         let result = Foam::parse("variable value1 value2 value3;");
-        let map = HashMap::from([(
+        let map = Dictionary::from([(
             "variable",
             vec![
                 Foam::Value("value1"),
@@ -163,7 +670,7 @@ mod test {
     #[test]
     fn multiple_variables() {
         let result = Foam::parse("var1 value1;\nvar2 value2 value3;");
-        let map = HashMap::from([
+        let map = Dictionary::from([
             ("var1", vec![Foam::Value("value1")]),
             ("var2", vec![Foam::Value("value2"), Foam::Value("value3")]),
         ]);
@@ -173,7 +680,7 @@ mod test {
     #[test]
     fn simple_list() {
         let result = Foam::parse("var (value1 value2);");
-        let map = HashMap::from([(
+        let map = Dictionary::from([(
             "var",
             vec![Foam::List(vec![
                 Foam::Value("value1"),
@@ -186,7 +693,7 @@ mod test {
     #[test]
     fn lists_with_lists() {
         let result = Foam::parse("var ( value1 ( inner2 ) );");
-        let map = HashMap::from([(
+        let map = Dictionary::from([(
             "var",
             vec![Foam::List(vec![
                 Foam::Value("value1"),
@@ -199,26 +706,27 @@ mod test {
     #[test]
     fn simple_dict() {
         let result = Foam::parse("entry { var value; }");
-        let inner = HashMap::from([("var", vec![Foam::Value("value")])]);
-        let map = HashMap::from([("entry", vec![Foam::Dictionary(inner)])]);
+        let inner = Dictionary::from([("var", vec![Foam::Value("value")])]);
+        let map = Dictionary::from([("entry", vec![Foam::Dictionary(inner)])]);
         assert_eq!(result, Ok(Foam::Dictionary(map)));
     }
 
     #[test]
     fn dict_with_multiple_values() {
         let result = Foam::parse("entry { var1 value1; var2 value2; }");
-        let inner = HashMap::from([
+        let inner = Dictionary::from([
             ("var1", vec![Foam::Value("value1")]),
             ("var2", vec![Foam::Value("value2")]),
         ]);
-        let outer = HashMap::from([("entry", vec![Foam::Dictionary(inner)])]);
+        let outer =
+            Dictionary::from([("entry", vec![Foam::Dictionary(inner)])]);
         assert_eq!(result, Ok(Foam::Dictionary(outer)));
     }
 
     #[test]
     fn dict_with_lists() {
         let result = Foam::parse("outer { a_list ( 1 2 3 ); }");
-        let inner = HashMap::from([(
+        let inner = Dictionary::from([(
             "a_list",
             vec![Foam::List(vec![
                 Foam::Value("1"),
@@ -226,7 +734,8 @@ mod test {
                 Foam::Value("3"),
             ])],
         )]);
-        let outer = HashMap::from([("outer", vec![Foam::Dictionary(inner)])]);
+        let outer =
+            Dictionary::from([("outer", vec![Foam::Dictionary(inner)])]);
         assert_eq!(result, Ok(Foam::Dictionary(outer)));
     }
 
@@ -235,11 +744,11 @@ mod test {
         let result = Foam::parse("attribution 1;list (1 2);dict {inner 1;}");
         let attribution = vec![Foam::Value("1")];
         let list = Foam::List(vec![Foam::Value("1"), Foam::Value("2")]);
-        let dict = Foam::Dictionary(HashMap::from([(
+        let dict = Foam::Dictionary(Dictionary::from([(
             "inner",
             vec![Foam::Value("1")],
         )]));
-        let main = Foam::Dictionary(HashMap::from([
+        let main = Foam::Dictionary(Dictionary::from([
             ("attribution", attribution),
             ("list", vec![list]),
             ("dict", vec![dict]),
@@ -247,6 +756,642 @@ mod test {
         assert_eq!(result, Ok(main));
     }
 
+    #[test]
+    fn dimension() {
+        let result = Foam::parse("dimensions [0 1 -1 0 0 0 0];");
+        let map = Dictionary::from([(
+            "dimensions",
+            vec![Foam::Dimension(vec!["0", "1", "-1", "0", "0", "0", "0"])],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn short_dimension() {
+        let result = Foam::parse("dimensions [0 2 -1 0 0];");
+        let map = Dictionary::from([(
+            "dimensions",
+            vec![Foam::Dimension(vec!["0", "2", "-1", "0", "0"])],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn named_dimension() {
+        let result = Foam::parse("nu [m^2 s^-1] 1e-05;");
+        let map = Dictionary::from([(
+            "nu",
+            vec![Foam::Dimension(vec!["m^2", "s^-1"]), Foam::Value("1e-05")],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn dimension_in_list() {
+        let result = Foam::parse("var ( [0 1 0 0 0] 2 );");
+        let map = Dictionary::from([(
+            "var",
+            vec![Foam::List(vec![
+                Foam::Dimension(vec!["0", "1", "0", "0", "0"]),
+                Foam::Value("2"),
+            ])],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn unclosed_dimension() {
+        let result = Foam::parse("dimensions [0 1 -1 0 0 0 0;");
+        assert_eq!(
+            result,
+            Err(FoamError::UnexpectedToken {
+                token: ";",
+                structure: "dimension",
+                location: Location {
+                    span: 26..27,
+                    line: 1,
+                    column: 27
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let result =
+            Foam::parse("var -6.0 +1.5 1e-06 1.0E+05 .5 -inf nan Infinity;");
+        let map = Dictionary::from([(
+            "var",
+            vec![
+                Foam::Value("-6.0"),
+                Foam::Value("+1.5"),
+                Foam::Value("1e-06"),
+                Foam::Value("1.0E+05"),
+                Foam::Value(".5"),
+                Foam::Value("-inf"),
+                Foam::Value("nan"),
+                Foam::Value("Infinity"),
+            ],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn special_words() {
+        let result = Foam::parse(
+            "type List<scalar> a-b patch:name x,y a|b a*b $var information;",
+        );
+        let map = Dictionary::from([(
+            "type",
+            vec![
+                Foam::Value("List<scalar>"),
+                Foam::Value("a-b"),
+                Foam::Value("patch:name"),
+                Foam::Value("x,y"),
+                Foam::Value("a|b"),
+                Foam::Value("a*b"),
+                Foam::Value("$var"),
+                Foam::Value("information"),
+            ],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn signed_vertices() {
+        let result = Foam::parse("vertices ( ( -6.0 -0.5 -0.1) );");
+        let map = Dictionary::from([(
+            "vertices",
+            vec![Foam::List(vec![Foam::List(vec![
+                Foam::Value("-6.0"),
+                Foam::Value("-0.5"),
+                Foam::Value("-0.1"),
+            ])])],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn function_keywords() {
+        let result = Foam::parse(
+            "divSchemes {
+                div(phi,U) Gauss linearUpwind grad(U);
+                div((nuEff*dev2(T(grad(U))))) Gauss linear;
+            }",
+        );
+        let inner = Dictionary::from([
+            (
+                "div(phi,U)",
+                vec![
+                    Foam::Value("Gauss"),
+                    Foam::Value("linearUpwind"),
+                    Foam::Value("grad(U)"),
+                ],
+            ),
+            (
+                "div((nuEff*dev2(T(grad(U)))))",
+                vec![Foam::Value("Gauss"), Foam::Value("linear")],
+            ),
+        ]);
+        let map =
+            Dictionary::from([("divSchemes", vec![Foam::Dictionary(inner)])]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn spaced_list_is_not_function() {
+        let result = Foam::parse("blocks ( hex (0 1) simpleGrading (1 1) );");
+        let map = Dictionary::from([(
+            "blocks",
+            vec![Foam::List(vec![
+                Foam::Value("hex"),
+                Foam::List(vec![Foam::Value("0"), Foam::Value("1")]),
+                Foam::Value("simpleGrading"),
+                Foam::List(vec![Foam::Value("1"), Foam::Value("1")]),
+            ])],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn braced_macros() {
+        let result = Foam::parse("a ${a(b)}; b ${${c}}; c $d;");
+        let map = Dictionary::from([
+            ("a", vec![Foam::Value("${a(b)}")]),
+            ("b", vec![Foam::Value("${${c}}")]),
+            ("c", vec![Foam::Value("$d")]),
+        ]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+
+        let result = Foam::parse("a ${a(b};");
+        assert!(matches!(
+            result,
+            Err(FoamError::InvalidCharacter { text: "$", .. })
+        ));
+    }
+
+    #[test]
+    fn comments_with_stars_and_slashes() {
+        let result = Foam::parse("/* a * b / c **/ var /* *\\ */ value;");
+        let map = Dictionary::from([("var", vec![Foam::Value("value")])]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let result = Foam::parse("var value; /* never closed");
+        assert_eq!(
+            result,
+            Err(FoamError::UnterminatedComment {
+                location: Location {
+                    span: 11..26,
+                    line: 1,
+                    column: 12
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_character() {
+        let result = Foam::parse("var1 value1;\n  var2 @value2;");
+        assert_eq!(
+            result,
+            Err(FoamError::InvalidCharacter {
+                text: "@",
+                location: Location {
+                    span: 20..21,
+                    line: 2,
+                    column: 8
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn unexpected_token_location() {
+        let result = Foam::parse("dict {\n    var ( 1 2 };\n}");
+        assert_eq!(
+            result,
+            Err(FoamError::UnexpectedToken {
+                token: "}",
+                structure: "list",
+                location: Location {
+                    span: 21..22,
+                    line: 2,
+                    column: 15
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn end_of_content() {
+        let result = Foam::parse("var ( 1 2");
+        assert_eq!(
+            result,
+            Err(FoamError::EndOfContent {
+                location: Location {
+                    span: 9..9,
+                    line: 1,
+                    column: 10
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn block_mesh_dict() {
+        let result =
+            Foam::parse(include_str!("../resources/blockMeshDict")).unwrap();
+        assert_eq!(result.get_first_value("scale"), Ok("1"));
+        let vertices = result.get_first_list("vertices").unwrap();
+        assert_eq!(
+            vertices[0],
+            Foam::List(vec![
+                Foam::Value("-6.0"),
+                Foam::Value("-0.5"),
+                Foam::Value("-0.1"),
+            ])
+        );
+    }
+
+    #[test]
+    fn fv_schemes() {
+        let result =
+            Foam::parse(include_str!("../resources/fvSchemes")).unwrap();
+        let div = result.get_first("divSchemes").unwrap();
+        assert_eq!(div.get_first_value("div(phi,U)"), Ok("Gauss"));
+        assert_eq!(
+            div.get("div((nuEff*dev2(T(grad(U)))))"),
+            Ok([Foam::Value("Gauss"), Foam::Value("linear")].as_slice())
+        );
+    }
+
+    #[test]
+    fn create_patch_dict() {
+        let result =
+            Foam::parse(include_str!("../resources/createPatchDict")).unwrap();
+        assert_eq!(result.get_first_value("pointSync"), Ok("false"));
+        assert_eq!(result.get_first_list("patches").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn recovering_without_errors() {
+        let (result, errors) = Foam::parse_recovering("var1 1; var2 (2 3);");
+        let map = Dictionary::from([
+            ("var1", vec![Foam::Value("1")]),
+            (
+                "var2",
+                vec![Foam::List(vec![Foam::Value("2"), Foam::Value("3")])],
+            ),
+        ]);
+        assert_eq!(result, Foam::Dictionary(map));
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn recovering_multiple_errors() {
+        let content = "solvers
+{
+    p
+    {
+        solver PCG;
+        tolerance ( 1e-06;
+        relTol 0.05;
+    }
+    U { solver @; smoother symGaussSeidel; }
+}
+PISO { nCorrectors 2; ) }
+";
+        let (result, errors) = Foam::parse_recovering(content);
+        let p = Foam::Dictionary(Dictionary::from([
+            ("solver", vec![Foam::Value("PCG")]),
+            ("relTol", vec![Foam::Value("0.05")]),
+        ]));
+        let u = Foam::Dictionary(Dictionary::from([(
+            "smoother",
+            vec![Foam::Value("symGaussSeidel")],
+        )]));
+        let piso = Foam::Dictionary(Dictionary::from([(
+            "nCorrectors",
+            vec![Foam::Value("2")],
+        )]));
+        let expected = Foam::Dictionary(Dictionary::from([
+            (
+                "solvers",
+                vec![Foam::Dictionary(Dictionary::from([
+                    ("p", vec![p]),
+                    ("U", vec![u]),
+                ]))],
+            ),
+            ("PISO", vec![piso]),
+        ]));
+        assert_eq!(result, expected);
+
+        let lines = errors
+            .iter()
+            .map(|error| error.location().unwrap().line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![6, 9, 11]);
+    }
+
+    #[test]
+    fn recovering_skips_nested_structures() {
+        let (result, errors) =
+            Foam::parse_recovering("a ] ( 1 ; 2 ) { b; }; c 3;");
+        let map = Dictionary::from([("c", vec![Foam::Value("3")])]);
+        assert_eq!(result, Foam::Dictionary(map));
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn stray_dictionary_end() {
+        assert!(matches!(
+            Foam::parse("a 1; } b 2;"),
+            Err(FoamError::UnexpectedToken { token: "}", .. })
+        ));
+
+        let (result, errors) =
+            Foam::parse_recovering("a 1; } b 2; c ] } d 4; e 5;");
+        let map = Dictionary::from([
+            ("a", vec![Foam::Value("1")]),
+            ("b", vec![Foam::Value("2")]),
+            ("e", vec![Foam::Value("5")]),
+        ]);
+        assert_eq!(result, Foam::Dictionary(map));
+        let tokens = errors
+            .iter()
+            .map(|error| match error {
+                FoamError::UnexpectedToken { token, .. } => *token,
+                _ => panic!("unexpected error {error:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, vec!["}", "]", "}"]);
+    }
+
+    #[test]
+    fn recovering_end_of_content() {
+        let (result, errors) = Foam::parse_recovering("a 1; b ( 2");
+        let map = Dictionary::from([("a", vec![Foam::Value("1")])]);
+        assert_eq!(result, Foam::Dictionary(map));
+        assert!(matches!(errors[..], [FoamError::EndOfContent { .. }]));
+    }
+
+    #[test]
+    fn strings() {
+        let result = Foam::parse(
+            r#"var word "a string" "" "escaped \" quote"; "inlet.*" { type wall; }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            result.get("var"),
+            Ok([
+                Foam::Value("word"),
+                Foam::String("a string"),
+                Foam::String(""),
+                Foam::String(r#"escaped \" quote"#),
+            ]
+            .as_slice())
+        );
+
+        let entries = result.as_dict().unwrap().entries();
+        assert!(!entries[0].quoted);
+        assert_eq!(entries[1].key, "inlet.*");
+        assert!(entries[1].quoted);
+    }
+
+    #[test]
+    fn verbatim() {
+        let result = Foam::parse(
+            "code
+    #{
+        os << \"{ ; }\" << $mass; // not a comment
+    #};
+",
+        );
+        let map = Dictionary::from([(
+            "code",
+            vec![Foam::Verbatim(
+                "\n        os << \"{ ; }\" << $mass; // not a comment\n    ",
+            )],
+        )]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn unterminated_verbatim() {
+        let result = Foam::parse("code #{ int a;");
+        assert_eq!(
+            result,
+            Err(FoamError::UnterminatedVerbatim {
+                location: Location {
+                    span: 5..14,
+                    line: 1,
+                    column: 6
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn include_directives() {
+        let result = Foam::parse(
+            "#include \"initialConditions\"
+#includeEtc \"caseDicts/setConstraintTypes\";
+functions { #includeFunc streamlines }
+a 1;",
+        );
+        let functions = Dictionary::from([(
+            "#includeFunc",
+            vec![Foam::Value("streamlines")],
+        )]);
+        let map = Dictionary::from([
+            ("#include", vec![Foam::String("initialConditions")]),
+            (
+                "#includeEtc",
+                vec![Foam::String("caseDicts/setConstraintTypes")],
+            ),
+            ("functions", vec![Foam::Dictionary(functions)]),
+            ("a", vec![Foam::Value("1")]),
+        ]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn remove_directive() {
+        let result = Foam::parse("#remove a\n#remove (b \"c.*\");\nd 1;");
+        let map = Dictionary::from([
+            ("#remove", vec![Foam::Value("a")]),
+            (
+                "#remove",
+                vec![Foam::List(vec![Foam::Value("b"), Foam::String("c.*")])],
+            ),
+            ("d", vec![Foam::Value("1")]),
+        ]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn conditionals() {
+        let result = Foam::parse(
+            "ddtSchemes
+{
+#ifeq ${FOAM_APPLICATION} simpleFoam // comment
+    default         steadyState;
+#else
+    default         Euler;
+#endif
+}
+#if #calc \"1 < 2\"
+a 1;
+#endif",
+        )
+        .unwrap();
+
+        let ddt_schemes = result.get_first_dict("ddtSchemes").unwrap();
+        let conditional = Conditional {
+            directive: "#ifeq",
+            condition: vec![
+                Foam::Value("${FOAM_APPLICATION}"),
+                Foam::Value("simpleFoam"),
+            ],
+            then: Dictionary::from([(
+                "default",
+                vec![Foam::Value("steadyState")],
+            )]),
+            otherwise: Some(Dictionary::from([(
+                "default",
+                vec![Foam::Value("Euler")],
+            )])),
+        };
+        assert_eq!(
+            ddt_schemes.get("#ifeq"),
+            Some([Foam::Conditional(conditional)].as_slice())
+        );
+
+        let conditional = Conditional {
+            directive: "#if",
+            condition: vec![Foam::Value("#calc"), Foam::String("1 < 2")],
+            then: Dictionary::from([("a", vec![Foam::Value("1")])]),
+            otherwise: None,
+        };
+        assert_eq!(
+            result.get("#if"),
+            Ok([Foam::Conditional(conditional)].as_slice())
+        );
+    }
+
+    #[test]
+    fn unterminated_conditional() {
+        let result = Foam::parse("a\n{\n  #if true\n  b 1;\n}");
+        assert_eq!(
+            result,
+            Err(FoamError::UnterminatedConditional {
+                location: Location {
+                    span: 6..9,
+                    line: 3,
+                    column: 3
+                }
+            })
+        );
+
+        let result = Foam::parse("#if true\n#else\n#else\n#endif");
+        assert!(matches!(
+            result,
+            Err(FoamError::UnexpectedToken {
+                token: "#else",
+                structure: "conditional",
+                ..
+            })
+        ));
+
+        let result = Foam::parse("a 1;\n#endif");
+        assert!(matches!(
+            result,
+            Err(FoamError::UnexpectedToken {
+                token: "#endif",
+                structure: "dictionary",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn code_stream() {
+        let result = Foam::parse(
+            "momentOfInertia #codeStream
+{
+    codeInclude
+    #{
+        #include \"diagTensor.H\"
+    #};
+
+    code
+    #{
+        os << $mass*diagTensor(1, 1, 1)/12.0;
+    #};
+};
+next 1;",
+        );
+        let code = Dictionary::from([
+            (
+                "codeInclude",
+                vec![Foam::Verbatim(
+                    "\n        #include \"diagTensor.H\"\n    ",
+                )],
+            ),
+            (
+                "code",
+                vec![Foam::Verbatim(
+                    "\n        os << $mass*diagTensor(1, 1, 1)/12.0;\n    ",
+                )],
+            ),
+        ]);
+        let map = Dictionary::from([
+            ("momentOfInertia", vec![Foam::CodeStream(code)]),
+            ("next", vec![Foam::Value("1")]),
+        ]);
+        assert_eq!(result, Ok(Foam::Dictionary(map)));
+    }
+
+    #[test]
+    fn code_stream_without_dictionary() {
+        let result = Foam::parse("a #codeStream 1;");
+        assert!(matches!(
+            result,
+            Err(FoamError::UnexpectedToken {
+                token: "1",
+                structure: "#codeStream",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn include_without_argument() {
+        let result = Foam::parse("a { #include }");
+        assert!(matches!(
+            result,
+            Err(FoamError::UnexpectedToken {
+                token: "}",
+                structure: "directive",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn control_dict() {
+        let result = Foam::parse(include_str!("../resources/controlDict"));
+        let root = result.unwrap();
+        let functions = root.get_first_dict("functions").unwrap();
+        assert_eq!(
+            functions.get("#include"),
+            Some([Foam::String("relVelocity")].as_slice())
+        );
+    }
+
     #[test]
     fn var_dict_list() {
         use tracing_subscriber::{fmt, prelude::*, EnvFilter};
@@ -269,16 +1414,16 @@ aList
 );
 ";
         let result = Foam::parse(example).unwrap();
-        let list_dict = Foam::Dictionary(HashMap::from([(
+        let list_dict = Foam::Dictionary(Dictionary::from([(
             "variable",
             vec![Foam::Value("4")],
         )]));
         let a_list = Foam::List(vec![list_dict]);
-        let inner_dict = Foam::Dictionary(HashMap::from([(
+        let inner_dict = Foam::Dictionary(Dictionary::from([(
             "variable",
             vec![Foam::Value("3")],
         )]));
-        let expected = Foam::Dictionary(HashMap::from([
+        let expected = Foam::Dictionary(Dictionary::from([
             ("variable", vec![Foam::Value("2")]),
             ("innerDict", vec![inner_dict]),
             ("aList", vec![a_list]),
@@ -0,0 +1,381 @@
+//! Vectors and tensors, read from the lists of their components.
+
+use std::fmt::Display;
+use std::ops::Add;
+use std::ops::Div;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::ops::Sub;
+
+use crate::calc::format;
+use crate::Foam;
+use crate::FoamError;
+use crate::SphericalTensor;
+use crate::SymmTensor;
+use crate::Tensor;
+use crate::Vector;
+
+/// Read the components of a list as numbers, checking there are exactly `N` of them.
+fn components<'l, const N: usize>(
+    kind: &'static str,
+    list: &'l [Foam<'_>],
+) -> Result<[f64; N], FoamError<'l>> {
+    if list.len() != N {
+        return Err(FoamError::ComponentCount {
+            kind,
+            expected: N,
+            found: list.len(),
+        });
+    }
+
+    let mut result = [0.0; N];
+    for (component, item) in result.iter_mut().zip(list) {
+        let text = match item {
+            Foam::Value(text) => *text,
+            Foam::Calculated(text) => text.as_str(),
+            _ => return Err(FoamError::NotAValue),
+        };
+        *component = text
+            .parse()
+            .map_err(|_| FoamError::InvalidComponent { kind, text })?;
+    }
+    Ok(result)
+}
+
+/// The conversions, the display and the component-wise arithmetic are the same for all the
+/// types; only the number of components changes.
+macro_rules! components {
+    ($type:ident, $kind:literal, $count:literal) => {
+        impl<'l> TryFrom<&'l [Foam<'_>]> for $type {
+            type Error = FoamError<'l>;
+
+            fn try_from(list: &'l [Foam<'_>]) -> Result<Self, Self::Error> {
+                components::<$count>($kind, list).map($type)
+            }
+        }
+
+        impl<'l> TryFrom<&'l Foam<'_>> for $type {
+            type Error = FoamError<'l>;
+
+            fn try_from(value: &'l Foam<'_>) -> Result<Self, Self::Error> {
+                match value {
+                    Foam::List(list) => Self::try_from(list.as_slice()),
+                    _ => Err(FoamError::NotAList),
+                }
+            }
+        }
+
+        impl Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let components: Vec<String> =
+                    self.0.iter().map(|x| format(*x)).collect();
+                write!(f, "({})", components.join(" "))
+            }
+        }
+
+        impl Add for $type {
+            type Output = Self;
+
+            fn add(mut self, other: Self) -> Self {
+                self.0.iter_mut().zip(other.0).for_each(|(a, b)| *a += b);
+                self
+            }
+        }
+
+        impl Sub for $type {
+            type Output = Self;
+
+            fn sub(mut self, other: Self) -> Self {
+                self.0.iter_mut().zip(other.0).for_each(|(a, b)| *a -= b);
+                self
+            }
+        }
+
+        impl Neg for $type {
+            type Output = Self;
+
+            fn neg(mut self) -> Self {
+                self.0.iter_mut().for_each(|a| *a = -*a);
+                self
+            }
+        }
+
+        impl Mul<f64> for $type {
+            type Output = Self;
+
+            fn mul(mut self, factor: f64) -> Self {
+                self.0.iter_mut().for_each(|a| *a *= factor);
+                self
+            }
+        }
+
+        impl Mul<$type> for f64 {
+            type Output = $type;
+
+            fn mul(self, value: $type) -> $type {
+                value * self
+            }
+        }
+
+        impl Div<f64> for $type {
+            type Output = Self;
+
+            fn div(mut self, divisor: f64) -> Self {
+                self.0.iter_mut().for_each(|a| *a /= divisor);
+                self
+            }
+        }
+    };
+}
+
+components!(Vector, "vector", 3);
+components!(Tensor, "tensor", 9);
+components!(SymmTensor, "symmTensor", 6);
+components!(SphericalTensor, "sphericalTensor", 1);
+
+impl Vector {
+    /// Create a vector from its components.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::Vector;
+    /// let root = Foam::parse("U uniform (1 0 0);").unwrap();
+    /// let velocity = Vector::try_from(root.get_first_list("U").unwrap()).unwrap();
+    /// assert_eq!(velocity, Vector::new(1.0, 0.0, 0.0));
+    /// assert_eq!((velocity * 2.0).to_string(), "(2 0 0)");
+    /// ```
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        Self([x, y, z])
+    }
+
+    /// The dot (inner) product of two vectors.
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.0.iter().zip(other.0).map(|(a, b)| a * b).sum()
+    }
+
+    /// The cross product of two vectors.
+    ///
+    /// ```
+    /// # use foamparser::Vector;
+    /// let x = Vector::new(1.0, 0.0, 0.0);
+    /// let y = Vector::new(0.0, 1.0, 0.0);
+    /// assert_eq!(x.cross(&y), Vector::new(0.0, 0.0, 1.0));
+    /// ```
+    pub fn cross(&self, other: &Self) -> Self {
+        let [ax, ay, az] = self.0;
+        let [bx, by, bz] = other.0;
+        Self([ay * bz - az * by, az * bx - ax * bz, ax * by - ay * bx])
+    }
+
+    /// The magnitude (length) of the vector.
+    pub fn mag(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+}
+
+impl Tensor {
+    /// The identity tensor.
+    pub const IDENTITY: Self =
+        Self([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+
+    /// The transpose of the tensor.
+    pub fn transpose(&self) -> Self {
+        let [xx, xy, xz, yx, yy, yz, zx, zy, zz] = self.0;
+        Self([xx, yx, zx, xy, yy, zy, xz, yz, zz])
+    }
+
+    /// The sum of the diagonal components.
+    ///
+    /// ```
+    /// # use foamparser::Foam;
+    /// # use foamparser::Tensor;
+    /// let root = Foam::parse("
+    ///     identity (
+    ///         1 0 0
+    ///         0 1 0
+    ///         0 0 1
+    ///     );
+    /// ").unwrap();
+    /// let identity = Tensor::try_from(root.get_first("identity").unwrap()).unwrap();
+    /// assert_eq!(identity, Tensor::IDENTITY);
+    /// assert_eq!(identity.trace(), 3.0);
+    /// ```
+    pub fn trace(&self) -> f64 {
+        self.0[0] + self.0[4] + self.0[8]
+    }
+
+    /// The determinant of the tensor.
+    pub fn det(&self) -> f64 {
+        let [xx, xy, xz, yx, yy, yz, zx, zy, zz] = self.0;
+        xx * (yy * zz - yz * zy) - xy * (yx * zz - yz * zx)
+            + xz * (yx * zy - yy * zx)
+    }
+}
+
+/// The inner product of a tensor and a vector.
+impl Mul<Vector> for Tensor {
+    type Output = Vector;
+
+    fn mul(self, vector: Vector) -> Vector {
+        let row = |i: usize| {
+            Vector([self.0[3 * i], self.0[3 * i + 1], self.0[3 * i + 2]])
+                .dot(&vector)
+        };
+        Vector([row(0), row(1), row(2)])
+    }
+}
+
+/// The inner product of two tensors.
+impl Mul for Tensor {
+    type Output = Tensor;
+
+    fn mul(self, other: Tensor) -> Tensor {
+        let mut result = [0.0; 9];
+        for (index, component) in result.iter_mut().enumerate() {
+            let (i, j) = (index / 3, index % 3);
+            *component =
+                (0..3).map(|k| self.0[3 * i + k] * other.0[3 * k + j]).sum();
+        }
+        Tensor(result)
+    }
+}
+
+impl SymmTensor {
+    /// The sum of the diagonal components.
+    pub fn trace(&self) -> f64 {
+        self.0[0] + self.0[3] + self.0[5]
+    }
+}
+
+/// Expand the upper triangle to the full tensor.
+///
+/// ```
+/// # use foamparser::SymmTensor;
+/// # use foamparser::Tensor;
+/// let symmetric = SymmTensor([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+/// assert_eq!(
+///     Tensor::from(symmetric),
+///     Tensor([1.0, 2.0, 3.0, 2.0, 4.0, 5.0, 3.0, 5.0, 6.0])
+/// );
+/// ```
+impl From<SymmTensor> for Tensor {
+    fn from(symmetric: SymmTensor) -> Self {
+        let [xx, xy, xz, yy, yz, zz] = symmetric.0;
+        Tensor([xx, xy, xz, xy, yy, yz, xz, yz, zz])
+    }
+}
+
+impl SphericalTensor {
+    /// The spherical identity tensor.
+    pub const IDENTITY: Self = Self([1.0]);
+
+    /// The sum of the diagonal components.
+    pub fn trace(&self) -> f64 {
+        3.0 * self.0[0]
+    }
+}
+
+impl From<SphericalTensor> for Tensor {
+    fn from(spherical: SphericalTensor) -> Self {
+        Tensor::IDENTITY * spherical.0[0]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Foam;
+    use crate::FoamError;
+    use crate::SphericalTensor;
+    use crate::SymmTensor;
+    use crate::Tensor;
+    use crate::Vector;
+
+    #[test]
+    fn component_counts() {
+        let root = Foam::parse("a (1 2); b (1 0 0 1 0 1); c x;").unwrap();
+        assert_eq!(
+            Vector::try_from(root.get_first("a").unwrap()),
+            Err(FoamError::ComponentCount {
+                kind: "vector",
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            SymmTensor::try_from(root.get_first("b").unwrap()),
+            Ok(SymmTensor([1.0, 0.0, 0.0, 1.0, 0.0, 1.0]))
+        );
+        assert_eq!(
+            Tensor::try_from(root.get_first("b").unwrap()),
+            Err(FoamError::ComponentCount {
+                kind: "tensor",
+                expected: 9,
+                found: 6
+            })
+        );
+        assert_eq!(
+            SphericalTensor::try_from(root.get_first("c").unwrap()),
+            Err(FoamError::NotAList)
+        );
+    }
+
+    #[test]
+    fn invalid_components() {
+        let root = Foam::parse("a (1 two 3); b (1 (2) 3);").unwrap();
+        assert_eq!(
+            Vector::try_from(root.get_first("a").unwrap()),
+            Err(FoamError::InvalidComponent {
+                kind: "vector",
+                text: "two"
+            })
+        );
+        assert_eq!(
+            Vector::try_from(root.get_first("b").unwrap()),
+            Err(FoamError::NotAValue)
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let a = Vector::new(1.0, 2.0, 3.0);
+        let b = Vector::new(4.0, 5.0, 6.0);
+        assert_eq!(a + b, Vector::new(5.0, 7.0, 9.0));
+        assert_eq!(b - a, Vector::new(3.0, 3.0, 3.0));
+        assert_eq!(-a, Vector::new(-1.0, -2.0, -3.0));
+        assert_eq!(2.0 * a, a * 2.0);
+        assert_eq!(b / 2.0, Vector::new(2.0, 2.5, 3.0));
+        assert_eq!(a.dot(&b), 32.0);
+
+        let t = Tensor([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0]);
+        assert_eq!(Tensor::IDENTITY * a, a);
+        assert_eq!(t * Tensor::IDENTITY, t);
+        assert_eq!(t * a, Vector::new(14.0, 32.0, 53.0));
+        assert_eq!(t.transpose().transpose(), t);
+        assert_eq!(t.det(), -3.0);
+        assert_eq!(
+            Tensor::from(SphericalTensor([2.0])),
+            Tensor::IDENTITY * 2.0
+        );
+        assert_eq!(SphericalTensor::IDENTITY.trace(), 3.0);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Vector::new(1.5, 0.0, -2.0).to_string(), "(1.5 0 -2)");
+        assert_eq!(Tensor::IDENTITY.to_string(), "(1 0 0 0 1 0 0 0 1)");
+        assert_eq!(
+            SymmTensor([1e-6; 6]).to_string(),
+            "(1e-6 1e-6 1e-6 1e-6 1e-6 1e-6)"
+        );
+        assert_eq!(SphericalTensor::IDENTITY.to_string(), "(1)");
+    }
+
+    #[test]
+    fn round_trip() {
+        let root = Foam::parse("t (1 2 3 4 5 6 7 8 9);").unwrap();
+        let tensor = Tensor::try_from(root.get_first("t").unwrap()).unwrap();
+        let written = format!("t {};", tensor);
+        let reparsed = Foam::parse(&written).unwrap();
+        assert_eq!(reparsed, root);
+    }
+}
@@ -1,19 +1,101 @@
+use std::ops::Range;
+
 use logos::Logos;
 
+use crate::Location;
+
+/// Errors found while breaking the content into tokens.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) enum LexerError {
+    /// Something that can't be converted to any token.
+    #[default]
+    InvalidToken,
+
+    /// A `/*` without the matching `*/`.
+    UnterminatedComment,
+
+    /// A `#{` without the matching `#}`.
+    UnterminatedVerbatim,
+}
+
+/// Keeps track of lines while the content is processed, so finding the location of a token doesn't
+/// require going through the whole content again.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Lines {
+    /// How far in the content we already looked for new lines.
+    offset: usize,
+
+    /// Number of new lines found so far.
+    lines: usize,
+
+    /// Where the current line starts.
+    line_start: usize,
+}
+
+impl Lines {
+    /// Find the location of a span in the content. Spans are expected to come in order; if not,
+    /// the content is checked from the start again.
+    pub(crate) fn locate(
+        &mut self,
+        content: &str,
+        span: Range<usize>,
+    ) -> Location {
+        if span.start < self.offset {
+            *self = Lines::default();
+        }
+
+        let skipped = &content[self.offset..span.start];
+        self.lines += skipped.matches('\n').count();
+        if let Some(pos) = skipped.rfind('\n') {
+            self.line_start = self.offset + pos + 1;
+        }
+        self.offset = span.start;
+
+        let column = content[self.line_start..span.start].chars().count() + 1;
+        Location {
+            span,
+            line: self.lines + 1,
+            column,
+        }
+    }
+}
+
 #[allow(dead_code)]
-#[derive(Logos, Debug)]
+#[derive(Logos, Debug, Clone)]
 #[logos(skip r"[ \t\n\r]")]
+#[logos(error = LexerError)]
+#[logos(extras = Lines)]
 pub(crate) enum Token<'a> {
-    #[regex(r#"\/\*[^\/\*]*\*\/"#, |lex| lex.slice())]
+    #[token("/*", block_comment)]
     MultilineComment(&'a str),
 
-    #[regex(r#""[^"]+""#, |lex| lex.slice().trim_start_matches('"').trim_end_matches('"'))]
-    #[regex("[a-zA-Z0-9_/.]+", |lex| lex.slice())]
+    // Numbers, following the same rules OpenFOAM uses to read scalars: optional sign, optional
+    // decimal part, optional exponent, plus the special `inf` and `nan` words.
+    #[regex(
+        r"[+-]?([0-9]+\.?[0-9]*|\.[0-9]+)([eE][+-]?[0-9]+)?",
+        |lex| lex.slice(),
+        priority = 10
+    )]
+    #[regex(r"[+-]?(?i:inf|infinity|nan)", |lex| lex.slice(), priority = 10)]
+    // Words. Besides the usual characters, OpenFOAM words can contain things like `List<scalar>`,
+    // `a|b`, `patch:name` and macros like `$var`, `$!var` and `${${var}}`.
+    #[regex(r"[a-zA-Z0-9_$][a-zA-Z0-9_/.^:<>,|*$+!-]*", word)]
     Keyword(&'a str),
 
+    // Quoted strings. Escaped characters (like `\"`) are kept as they are.
+    #[regex(r#""([^"\\]|\\(.|\n))*""#, |lex| { let slice = lex.slice(); &slice[1..slice.len() - 1] })]
+    String(&'a str),
+
     #[regex(r#"//[^\n]*"#, |lex| lex.slice())]
     Comment(&'a str),
 
+    #[token("#{", verbatim)]
+    Verbatim(&'a str),
+
+    // Directives, like `#include` or `#remove`.
+    #[regex(r"#[a-zA-Z]+", |lex| lex.slice())]
+    Directive(&'a str),
+
     #[token(";")]
     End,
 
@@ -28,4 +110,102 @@ pub(crate) enum Token<'a> {
 
     #[token(")")]
     ListEnd,
+
+    #[token("[")]
+    DimensionStart,
+
+    #[token("]")]
+    DimensionEnd,
+}
+
+/// Process a block comment, which goes till the first `*/`. Any characters -- including `*`
+/// and `/`, like the ones in the banner OpenFOAM adds to its files -- can appear inside it.
+fn block_comment<'a>(
+    lex: &mut logos::Lexer<'a, Token<'a>>,
+) -> Result<&'a str, LexerError> {
+    match lex.remainder().find("*/") {
+        Some(end) => {
+            lex.bump(end + 2);
+            Ok(lex.slice())
+        }
+        None => {
+            lex.bump(lex.remainder().len());
+            Err(LexerError::UnterminatedComment)
+        }
+    }
+}
+
+/// Process a verbatim block, which goes till the first `#}`. The content between the markers is
+/// kept exactly as it is.
+fn verbatim<'a>(
+    lex: &mut logos::Lexer<'a, Token<'a>>,
+) -> Result<&'a str, LexerError> {
+    match lex.remainder().find("#}") {
+        Some(end) => {
+            let content = &lex.remainder()[..end];
+            lex.bump(end + 2);
+            Ok(content)
+        }
+        None => {
+            lex.bump(lex.remainder().len());
+            Err(LexerError::UnterminatedVerbatim)
+        }
+    }
+}
+
+/// Process a word. If the word is immediately followed by a balanced group of parenthesis (e.g.,
+/// `div(phi,U)` or `div((nuEff*dev2(T(grad(U)))))`), the group is considered part of the word.
+/// In the same way, a `$` followed by a balanced group of braces (e.g., `${${var}}` or
+/// `${a(b)}`) is a single word; a `$` followed by a brace that is never closed is an error.
+fn word<'a>(
+    lex: &mut logos::Lexer<'a, Token<'a>>,
+) -> Result<&'a str, LexerError> {
+    if lex.slice() == "$" {
+        match balanced_group(lex.remainder(), '{', '}') {
+            Some(size) => lex.bump(size),
+            None if lex.remainder().starts_with('{') => {
+                return Err(LexerError::InvalidToken)
+            }
+            None => {}
+        }
+        return Ok(lex.slice());
+    }
+
+    let starts_with_letter = lex
+        .slice()
+        .starts_with(|char: char| char.is_ascii_alphabetic() || char == '_');
+    if starts_with_letter {
+        if let Some(size) = balanced_group(lex.remainder(), '(', ')') {
+            lex.bump(size);
+        }
+    }
+    Ok(lex.slice())
+}
+
+/// Checks if the content starts with a balanced group (between `open` and `close`), returning its
+/// size. Like OpenFOAM, the group can't have spaces or any characters that have special meaning in
+/// the file, except for nested groups: parenthesis inside parenthesis and, inside braces, both
+/// braces and parenthesis (like `${a(b)}`).
+fn balanced_group(content: &str, open: char, close: char) -> Option<usize> {
+    if !content.starts_with(open) {
+        return None;
+    }
+
+    let mut closing = Vec::new();
+    for (pos, char) in content.char_indices() {
+        match char {
+            _ if char == open => closing.push(close),
+            '(' if open == '{' => closing.push(')'),
+            _ if closing.last() == Some(&char) => {
+                closing.pop();
+                if closing.is_empty() {
+                    return Some(pos + 1);
+                }
+            }
+            ';' | '{' | '}' | '(' | ')' | '[' | ']' | '"' => return None,
+            _ if char.is_whitespace() => return None,
+            _ => {}
+        }
+    }
+    None
 }